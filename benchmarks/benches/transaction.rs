@@ -1,11 +1,13 @@
 use benchmarks::{slow_config, very_slow_config};
-use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use ironfish::{
     assets::{asset::Asset, asset_identifier::NATIVE_ASSET},
     test_util::make_fake_witness,
     transaction::{batch_verify_transactions, verify_transaction, TransactionVersion},
     Note, ProposedTransaction, SaplingKey, Transaction,
 };
+use rayon::ThreadPoolBuilder;
+use std::sync::Arc;
 
 pub fn simple(c: &mut Criterion) {
     c.bench_function("transaction::simple", |b| {
@@ -79,6 +81,66 @@ pub fn all_descriptions(c: &mut Criterion) {
     });
 }
 
+pub fn parallel_proofs(c: &mut Criterion) {
+    const SPEND_AMOUNT: usize = 10;
+
+    let max_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+
+    let mut group = c.benchmark_group("transaction::parallel_proofs");
+
+    for threads in [1, max_threads] {
+        let thread_pool = Arc::new(
+            ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap(),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &thread_pool,
+            |b, thread_pool| {
+                b.iter_batched(
+                    // Setup
+                    || {
+                        let key = SaplingKey::generate_key();
+                        let public_address = key.public_address();
+
+                        let mut proposed = ProposedTransaction::new(TransactionVersion::latest());
+                        proposed.set_thread_pool(thread_pool.clone());
+
+                        for _ in 0..SPEND_AMOUNT {
+                            let spend_note =
+                                Note::new(public_address, 42, "", NATIVE_ASSET, public_address);
+                            let witness = make_fake_witness(&spend_note);
+                            proposed.add_spend(spend_note, &witness).unwrap();
+
+                            let out_note =
+                                Note::new(public_address, 41, "", NATIVE_ASSET, public_address);
+                            proposed.add_output(out_note).unwrap();
+                        }
+
+                        (key, proposed)
+                    },
+                    // Benchmark
+                    |(key, mut proposed)| {
+                        let tx = proposed.post(&key, None, 1).unwrap();
+
+                        assert_eq!(tx.spends().len(), SPEND_AMOUNT);
+                        // The outputs plus one change note
+                        assert_eq!(tx.outputs().len(), SPEND_AMOUNT + 1);
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
 pub fn verify(c: &mut Criterion) {
     c.bench_function("transaction::verify", |b| {
         b.iter_batched(
@@ -154,6 +216,6 @@ criterion_group! {
 criterion_group! {
     name = very_slow_benches;
     config = very_slow_config();
-    targets = batch_verify,
+    targets = batch_verify, parallel_proofs
 }
 criterion_main!(slow_benches, very_slow_benches);
@@ -29,6 +29,19 @@ export interface BoxedMessage {
 }
 export declare function boxMessage(plaintext: string, senderSecretKey: Uint8Array, recipientPublicKey: string): BoxedMessage
 export declare function unboxMessage(boxedMessage: string, nonce: string, senderPublicKey: string, recipientSecretKey: Uint8Array): string
+export const enum NullifierStatus {
+  /** The nullifier does not belong to a note of the account */
+  Unknown = 0,
+  /** The note was unspent, and is now spent */
+  Spent = 1,
+  /** The note was already spent */
+  DoubleSpend = 2
+}
+export interface NullifierSpend {
+  status: NullifierStatus
+  /** Position of the spent note, unless the status is `Unknown` */
+  position?: number
+}
 export declare function initSignalHandler(): void
 export const ASSET_ID_LENGTH: number
 export const ASSET_METADATA_LENGTH: number
@@ -38,6 +51,23 @@ export const NOTE_ENCRYPTION_KEY_LENGTH: number
 export const MAC_LENGTH: number
 export const ENCRYPTED_NOTE_PLAINTEXT_LENGTH: number
 export const ENCRYPTED_NOTE_LENGTH: number
+export interface DecryptedNoteMatch {
+  noteIndex: number
+  keyIndex: number
+  note: Buffer
+}
+export interface ScannedNotes {
+  /** Notes decrypted with one of the incoming view keys */
+  received: Array<DecryptedNoteMatch>
+  /** Notes decrypted with one of the outgoing view keys */
+  sent: Array<DecryptedNoteMatch>
+}
+/**
+ * Attempt to decrypt all the encrypted notes of a block with all the given
+ * view keys at once. The indexes in the returned matches refer to the
+ * positions of the note and of the key in the arrays passed in.
+ */
+export declare function scanNotes(serializedNotes: Array<Buffer>, incomingViewKeys: Array<Buffer>, outgoingViewKeys?: Array<Buffer> | undefined | null): ScannedNotes
 export const PUBLIC_ADDRESS_LENGTH: number
 export const RANDOMNESS_LENGTH: number
 export const MEMO_LENGTH: number
@@ -55,6 +85,20 @@ export const TRANSACTION_EXPIRATION_LENGTH: number
 export const TRANSACTION_FEE_LENGTH: number
 export const LATEST_TRANSACTION_VERSION: number
 export declare function verifyTransactions(serializedTransactions: Array<Buffer>): boolean
+export interface TransactionVerificationFailure {
+  index: number
+  /**
+   * The kind of error, followed by where in the transaction it happened
+   * when known, for example `InvalidData at spends[3].nullifier`
+   */
+  error: string
+}
+/**
+ * Verify a batch of transactions, returning the index and error of every
+ * transaction that failed verification. An empty array means that all
+ * transactions are valid.
+ */
+export declare function verifyTransactionsWithFailures(serializedTransactions: Array<Buffer>): Array<TransactionVerificationFailure>
 export const enum LanguageCode {
   English = 0,
   ChineseSimplified = 1,
@@ -78,8 +122,50 @@ export declare function spendingKeyToWords(privateKey: string, languageCode: Lan
 export declare function wordsToSpendingKey(words: string, languageCode: LanguageCode): string
 export declare function generatePublicAddressFromIncomingViewKey(ivkString: string): string
 export declare function generateKeyFromPrivateKey(privateKey: string): Key
+export declare function deriveKeyFromPath(privateKey: string, path: string): Key
 export declare function initializeSapling(): void
+export interface MiningStats {
+  elapsedMs: number
+  /** Hashes computed by each thread since the thread pool was created */
+  threadHashes: Array<number>
+  totalHashes: number
+  /** Hashes per second, averaged over the last 10 seconds */
+  hashRate: number
+  blocksFound: number
+  sharesFound: number
+  /** Blocks and shares discarded because they were found for previous work */
+  staleDiscarded: number
+  /** Threads that have built their FishHash context and are able to mine */
+  threadsReady: number
+  threadCount: number
+}
+export interface MiningBenchmarkResult {
+  algorithm: string
+  threadCount: number
+  batchSize: number
+  hashesPerSecond: number
+}
+/**
+ * Measure the hash rate of the mining algorithms for each combination of
+ * thread count and batch size, mining for `durationMs` milliseconds each.
+ * This blocks until all the measurements are done.
+ */
+export declare function benchmarkMining(threadCounts: Array<number>, batchSizes: Array<number>, durationMs: number, useFishHash: boolean, fishHashFullContext: boolean): Array<MiningBenchmarkResult>
 export declare function isValidPublicAddress(hexAddress: string): boolean
+export const enum Bech32KeyType {
+  PublicAddress = 0,
+  ViewKey = 1,
+  IncomingViewKey = 2,
+  SpendingKey = 3
+}
+/** Encode a hex address or key as a Bech32m string for the given network id. */
+export declare function encodeBech32(keyType: Bech32KeyType, hexKey: string, networkId: number): string
+/**
+ * Decode a Bech32m address or key for the given network id, returning it as
+ * hex. Fails if the string is for a different type of key or network.
+ */
+export declare function decodeBech32(keyType: Bech32KeyType, value: string, networkId: number): string
+export declare function isValidBech32PublicAddress(address: string, networkId: number): boolean
 /**
  * Return the number of processing units available to the system and to the current process.
  *
@@ -97,7 +183,12 @@ export declare function getCpuCount(): CpuCount
 export declare function generateRandomizedPublicKey(viewKeyString: string, publicKeyRandomnessString: string): string
 export class FishHashContext {
   constructor(full: boolean)
-  prebuildDataset(threads: number): void
+  /**
+   * Build the full dataset of a full context. If `path` is given, the
+   * dataset is loaded from that file instead, and is built and saved to it
+   * if the file is missing or corrupted.
+   */
+  prebuildDataset(threads: number, path?: string | undefined | null): void
   hash(header: Buffer): Buffer
 }
 export class BoxKeyPair {
@@ -106,6 +197,34 @@ export class BoxKeyPair {
   get publicKey(): Buffer
   get secretKey(): Buffer
 }
+export type NativeNullifierIndex = NullifierIndex
+export class NullifierIndex {
+  constructor(viewKey: string)
+  /**
+   * Add an unspent note at the given position in the note commitment tree,
+   * returning its nullifier.
+   */
+  addNote(note: Note, position: bigint): Buffer
+  /** Remove the note at the given position, returning whether it was found. */
+  removeNote(position: bigint): boolean
+  markSpent(nullifier: Buffer): NullifierSpend
+  /**
+   * Mark the note with the given nullifier as unspent, returning its
+   * position if it was spent.
+   */
+  markUnspent(nullifier: Buffer): number | null
+  /**
+   * Mark the notes spent by a serialized transaction as spent, returning
+   * the status of each spend.
+   */
+  markTransactionSpent(transactionBytes: Buffer): Array<NullifierSpend>
+  /**
+   * Whether the note with the given nullifier is spent, or `null` if it is
+   * not a note of the account.
+   */
+  isSpent(nullifier: Buffer): boolean | null
+  get size(): number
+}
 export type NativeRollingFilter = RollingFilter
 export class RollingFilter {
   constructor(items: number, rate: number)
@@ -231,6 +350,13 @@ export class Transaction {
   post(spenderHexKey: string, changeGoesTo: string | undefined | null, intendedTransactionFee: bigint): Buffer
   build(proofAuthorizingKeyStr: string, viewKeyStr: string, outgoingViewKeyStr: string, intendedTransactionFee: bigint, changeGoesTo?: string | undefined | null): Buffer
   setExpiration(sequence: number): void
+  /**
+   * Serialize the transaction before any proof is created, so that it can
+   * be posted elsewhere. The result contains the notes and witnesses of the
+   * transaction and must be kept private.
+   */
+  serialize(): Buffer
+  static deserialize(jsBytes: Buffer): Transaction
 }
 export type NativeUnsignedTransaction = UnsignedTransaction
 export class UnsignedTransaction {
@@ -248,13 +374,21 @@ export class FoundBlockResult {
   miningRequestId: number
   constructor(randomness: string, miningRequestId: number)
 }
+export class FoundShareResult {
+  randomness: string
+  miningRequestId: number
+  hash: string
+  constructor(randomness: string, miningRequestId: number, hash: string)
+}
 export class ThreadPoolHandler {
-  constructor(threadCount: number, batchSize: number, pauseOnSuccess: boolean, useFishHash: boolean, fishHashFullContext: boolean)
-  newWork(headerBytes: Buffer, target: Buffer, miningRequestId: number, fishHash: boolean, xnLength: number): void
+  constructor(threadCount: number, batchSize: number, pauseOnSuccess: boolean, useFishHash: boolean, fishHashFullContext: boolean, fishHashDatasetPath?: string | undefined | null)
+  newWork(headerBytes: Buffer, target: Buffer, miningRequestId: number, fishHash: boolean, xnLength: number, shareTarget?: Buffer | undefined | null): void
   stop(): void
   pause(): void
   getFoundBlock(): FoundBlockResult | null
+  getFoundShare(): FoundShareResult | null
   getHashRateSubmission(): number
+  stats(): MiningStats
 }
 export class CpuCount {
   /**
@@ -252,7 +252,7 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { FishHashContext, deserializePublicPackage, deserializeRound2CombinedPublicPackage, KEY_LENGTH, NONCE_LENGTH, BoxKeyPair, randomBytes, boxMessage, unboxMessage, RollingFilter, initSignalHandler, ASSET_ID_LENGTH, ASSET_METADATA_LENGTH, ASSET_NAME_LENGTH, ASSET_LENGTH, Asset, NOTE_ENCRYPTION_KEY_LENGTH, MAC_LENGTH, ENCRYPTED_NOTE_PLAINTEXT_LENGTH, ENCRYPTED_NOTE_LENGTH, NoteEncrypted, PUBLIC_ADDRESS_LENGTH, RANDOMNESS_LENGTH, MEMO_LENGTH, AMOUNT_VALUE_LENGTH, DECRYPTED_NOTE_LENGTH, Note, PROOF_LENGTH, TRANSACTION_SIGNATURE_LENGTH, TRANSACTION_PUBLIC_KEY_RANDOMNESS_LENGTH, TRANSACTION_EXPIRATION_LENGTH, TRANSACTION_FEE_LENGTH, LATEST_TRANSACTION_VERSION, TransactionPosted, Transaction, verifyTransactions, UnsignedTransaction, LanguageCode, generateKey, spendingKeyToWords, wordsToSpendingKey, generatePublicAddressFromIncomingViewKey, generateKeyFromPrivateKey, initializeSapling, FoundBlockResult, ThreadPoolHandler, isValidPublicAddress, CpuCount, getCpuCount, generateRandomizedPublicKey, multisig, xchacha20poly1305 } = nativeBinding
+const { FishHashContext, deserializePublicPackage, deserializeRound2CombinedPublicPackage, KEY_LENGTH, NONCE_LENGTH, BoxKeyPair, randomBytes, boxMessage, unboxMessage, NullifierStatus, NullifierIndex, RollingFilter, initSignalHandler, ASSET_ID_LENGTH, ASSET_METADATA_LENGTH, ASSET_NAME_LENGTH, ASSET_LENGTH, Asset, NOTE_ENCRYPTION_KEY_LENGTH, MAC_LENGTH, ENCRYPTED_NOTE_PLAINTEXT_LENGTH, ENCRYPTED_NOTE_LENGTH, NoteEncrypted, scanNotes, PUBLIC_ADDRESS_LENGTH, RANDOMNESS_LENGTH, MEMO_LENGTH, AMOUNT_VALUE_LENGTH, DECRYPTED_NOTE_LENGTH, Note, PROOF_LENGTH, TRANSACTION_SIGNATURE_LENGTH, TRANSACTION_PUBLIC_KEY_RANDOMNESS_LENGTH, TRANSACTION_EXPIRATION_LENGTH, TRANSACTION_FEE_LENGTH, LATEST_TRANSACTION_VERSION, TransactionPosted, Transaction, verifyTransactions, verifyTransactionsWithFailures, UnsignedTransaction, LanguageCode, generateKey, spendingKeyToWords, wordsToSpendingKey, generatePublicAddressFromIncomingViewKey, generateKeyFromPrivateKey, deriveKeyFromPath, initializeSapling, FoundBlockResult, FoundShareResult, ThreadPoolHandler, benchmarkMining, isValidPublicAddress, Bech32KeyType, encodeBech32, decodeBech32, isValidBech32PublicAddress, CpuCount, getCpuCount, generateRandomizedPublicKey, multisig, xchacha20poly1305 } = nativeBinding
 
 module.exports.FishHashContext = FishHashContext
 module.exports.deserializePublicPackage = deserializePublicPackage
@@ -263,6 +263,8 @@ module.exports.BoxKeyPair = BoxKeyPair
 module.exports.randomBytes = randomBytes
 module.exports.boxMessage = boxMessage
 module.exports.unboxMessage = unboxMessage
+module.exports.NullifierStatus = NullifierStatus
+module.exports.NullifierIndex = NullifierIndex
 module.exports.RollingFilter = RollingFilter
 module.exports.initSignalHandler = initSignalHandler
 module.exports.ASSET_ID_LENGTH = ASSET_ID_LENGTH
@@ -275,6 +277,7 @@ module.exports.MAC_LENGTH = MAC_LENGTH
 module.exports.ENCRYPTED_NOTE_PLAINTEXT_LENGTH = ENCRYPTED_NOTE_PLAINTEXT_LENGTH
 module.exports.ENCRYPTED_NOTE_LENGTH = ENCRYPTED_NOTE_LENGTH
 module.exports.NoteEncrypted = NoteEncrypted
+module.exports.scanNotes = scanNotes
 module.exports.PUBLIC_ADDRESS_LENGTH = PUBLIC_ADDRESS_LENGTH
 module.exports.RANDOMNESS_LENGTH = RANDOMNESS_LENGTH
 module.exports.MEMO_LENGTH = MEMO_LENGTH
@@ -290,6 +293,7 @@ module.exports.LATEST_TRANSACTION_VERSION = LATEST_TRANSACTION_VERSION
 module.exports.TransactionPosted = TransactionPosted
 module.exports.Transaction = Transaction
 module.exports.verifyTransactions = verifyTransactions
+module.exports.verifyTransactionsWithFailures = verifyTransactionsWithFailures
 module.exports.UnsignedTransaction = UnsignedTransaction
 module.exports.LanguageCode = LanguageCode
 module.exports.generateKey = generateKey
@@ -297,10 +301,17 @@ module.exports.spendingKeyToWords = spendingKeyToWords
 module.exports.wordsToSpendingKey = wordsToSpendingKey
 module.exports.generatePublicAddressFromIncomingViewKey = generatePublicAddressFromIncomingViewKey
 module.exports.generateKeyFromPrivateKey = generateKeyFromPrivateKey
+module.exports.deriveKeyFromPath = deriveKeyFromPath
 module.exports.initializeSapling = initializeSapling
 module.exports.FoundBlockResult = FoundBlockResult
+module.exports.FoundShareResult = FoundShareResult
 module.exports.ThreadPoolHandler = ThreadPoolHandler
+module.exports.benchmarkMining = benchmarkMining
 module.exports.isValidPublicAddress = isValidPublicAddress
+module.exports.Bech32KeyType = Bech32KeyType
+module.exports.encodeBech32 = encodeBech32
+module.exports.decodeBech32 = decodeBech32
+module.exports.isValidBech32PublicAddress = isValidBech32PublicAddress
 module.exports.CpuCount = CpuCount
 module.exports.getCpuCount = getCpuCount
 module.exports.generateRandomizedPublicKey = generateRandomizedPublicKey
@@ -3,12 +3,18 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use fish_hash::Context;
+use ironfish::mining::dataset::FishHashDataset;
 use napi::{bindgen_prelude::Buffer, JsBuffer};
 use napi_derive::napi;
 
+enum Inner {
+    Context(Context),
+    Dataset(FishHashDataset),
+}
+
 #[napi]
 pub struct FishHashContext {
-    inner: Context,
+    inner: Inner,
 }
 
 #[napi]
@@ -16,21 +22,42 @@ impl FishHashContext {
     #[napi(constructor)]
     pub fn new(full: bool) -> Self {
         Self {
-            inner: Context::new(full, None),
+            inner: Inner::Context(Context::new(full, None)),
         }
     }
 
+    /// Build the full dataset of a full context. If `path` is given, the
+    /// dataset is loaded from that file instead, and is built and saved to it
+    /// if the file is missing or corrupted.
     #[napi]
-    pub fn prebuild_dataset(&mut self, threads: u32) {
-        self.inner.prebuild_dataset(threads as usize)
+    pub fn prebuild_dataset(&mut self, threads: u32, path: Option<String>) {
+        let Inner::Context(context) = &mut self.inner else {
+            return;
+        };
+        if context.full_dataset.is_none() {
+            return;
+        }
+
+        match path {
+            Some(path) => {
+                self.inner = Inner::Dataset(FishHashDataset::load_or_build(path, threads as usize))
+            }
+            None => context.prebuild_dataset(threads as usize),
+        }
     }
 
     #[napi]
     pub fn hash(&mut self, header: JsBuffer) -> Buffer {
         let bytes = header.into_value().unwrap();
 
-        let mut output = [0u8; 32];
-        fish_hash::hash(&mut output, &mut self.inner, bytes.as_ref());
+        let output = match &mut self.inner {
+            Inner::Context(context) => {
+                let mut output = [0u8; 32];
+                fish_hash::hash(&mut output, context, bytes.as_ref());
+                output
+            }
+            Inner::Dataset(dataset) => dataset.hash(bytes.as_ref()),
+        };
 
         Buffer::from(output.to_vec())
     }
@@ -14,7 +14,9 @@ use std::fmt::Display;
 use std::num::NonZeroUsize;
 
 use ironfish::keys::generate_randomized_public_key;
+use ironfish::keys::DerivationPath;
 use ironfish::keys::Language;
+use ironfish::keys::Network;
 use ironfish::serializing::bytes_to_hex;
 use ironfish::serializing::fr::FrSerializable;
 use ironfish::IncomingViewKey;
@@ -31,6 +33,7 @@ use ironfish::sapling_bls12;
 pub mod fish_hash;
 pub mod multisig;
 pub mod nacl;
+pub mod nullifier_index;
 pub mod rolling_filter;
 pub mod signal_catcher;
 pub mod structs;
@@ -136,6 +139,25 @@ pub fn generate_key_from_private_key(private_key: String) -> Result<Key> {
     })
 }
 
+#[napi]
+pub fn derive_key_from_path(private_key: String, path: String) -> Result<Key> {
+    let path: DerivationPath = path.parse().map_err(to_napi_err)?;
+    let sapling_key = SaplingKey::from_hex(&private_key)
+        .and_then(|key| key.derive_path(&path))
+        .map_err(to_napi_err)?;
+
+    Ok(Key {
+        spending_key: sapling_key.hex_spending_key(),
+        view_key: sapling_key.view_key().hex_key(),
+        incoming_view_key: sapling_key.incoming_view_key().hex_key(),
+        outgoing_view_key: sapling_key.outgoing_view_key().hex_key(),
+        public_address: sapling_key.public_address().hex_public_address(),
+        proof_authorizing_key: bytes_to_hex(
+            &sapling_key.sapling_proof_generation_key().nsk.to_bytes(),
+        ),
+    })
+}
+
 #[napi]
 pub fn initialize_sapling() {
     // Deref the `SAPLING` lazy-static, to ensure it gets initialized
@@ -148,6 +170,30 @@ pub struct FoundBlockResult {
     pub mining_request_id: f64,
 }
 
+#[napi(constructor)]
+pub struct FoundShareResult {
+    pub randomness: String,
+    pub mining_request_id: f64,
+    pub hash: String,
+}
+
+#[napi(object)]
+pub struct MiningStats {
+    pub elapsed_ms: f64,
+    /// Hashes computed by each thread since the thread pool was created
+    pub thread_hashes: Vec<f64>,
+    pub total_hashes: f64,
+    /// Hashes per second, averaged over the last 10 seconds
+    pub hash_rate: f64,
+    pub blocks_found: f64,
+    pub shares_found: f64,
+    /// Blocks and shares discarded because they were found for previous work
+    pub stale_discarded: f64,
+    /// Threads that have built their FishHash context and are able to mine
+    pub threads_ready: u32,
+    pub thread_count: u32,
+}
+
 #[napi]
 pub struct ThreadPoolHandler {
     threadpool: mining::threadpool::ThreadPool,
@@ -161,16 +207,28 @@ impl ThreadPoolHandler {
         pause_on_success: bool,
         use_fish_hash: bool,
         fish_hash_full_context: bool,
+        fish_hash_dataset_path: Option<String>,
     ) -> Self {
-        ThreadPoolHandler {
-            threadpool: mining::threadpool::ThreadPool::new(
+        let threadpool = match fish_hash_dataset_path {
+            Some(path) if use_fish_hash => mining::threadpool::ThreadPool::with_algorithms(
+                thread_count as usize,
+                batch_size,
+                pause_on_success,
+                mining::algorithm::AlgorithmRegistry::with_fish_hash_dataset(
+                    path,
+                    thread_count as usize,
+                ),
+            ),
+            _ => mining::threadpool::ThreadPool::new(
                 thread_count as usize,
                 batch_size,
                 pause_on_success,
                 use_fish_hash,
                 fish_hash_full_context,
             ),
-        }
+        };
+
+        ThreadPoolHandler { threadpool }
     }
 
     #[napi]
@@ -181,10 +239,12 @@ impl ThreadPoolHandler {
         mining_request_id: u32,
         fish_hash: bool,
         xn_length: u8,
+        share_target: Option<Buffer>,
     ) {
         self.threadpool.new_work(
             &header_bytes,
             &target,
+            share_target.as_deref(),
             mining_request_id,
             fish_hash,
             xn_length,
@@ -212,10 +272,84 @@ impl ThreadPoolHandler {
         None
     }
 
+    #[napi]
+    pub fn get_found_share(&self) -> Option<FoundShareResult> {
+        self.threadpool
+            .get_found_share()
+            .map(|share| FoundShareResult {
+                randomness: format!("{:016x}", share.randomness),
+                mining_request_id: share.mining_request_id as f64,
+                hash: bytes_to_hex(&share.hash),
+            })
+    }
+
     #[napi]
     pub fn get_hash_rate_submission(&self) -> u32 {
         self.threadpool.get_hash_rate_submission()
     }
+
+    #[napi]
+    pub fn stats(&mut self) -> MiningStats {
+        let stats = self.threadpool.stats();
+
+        MiningStats {
+            elapsed_ms: stats.elapsed.as_secs_f64() * 1000.0,
+            total_hashes: stats.total_hashes() as f64,
+            thread_hashes: stats
+                .thread_hashes
+                .iter()
+                .map(|hashes| *hashes as f64)
+                .collect(),
+            hash_rate: stats.hash_rate,
+            blocks_found: stats.blocks_found as f64,
+            shares_found: stats.shares_found as f64,
+            stale_discarded: stats.stale_discarded as f64,
+            threads_ready: stats.threads_ready as u32,
+            thread_count: stats.thread_hashes.len() as u32,
+        }
+    }
+}
+
+#[napi(object)]
+pub struct MiningBenchmarkResult {
+    pub algorithm: String,
+    pub thread_count: u32,
+    pub batch_size: u32,
+    pub hashes_per_second: f64,
+}
+
+/// Measure the hash rate of the mining algorithms for each combination of
+/// thread count and batch size, mining for `durationMs` milliseconds each.
+/// This blocks until all the measurements are done.
+#[napi]
+pub fn benchmark_mining(
+    thread_counts: Vec<u32>,
+    batch_sizes: Vec<u32>,
+    duration_ms: u32,
+    use_fish_hash: bool,
+    fish_hash_full_context: bool,
+) -> Vec<MiningBenchmarkResult> {
+    let algorithms =
+        mining::algorithm::AlgorithmRegistry::with_defaults(use_fish_hash, fish_hash_full_context);
+    let thread_counts = thread_counts
+        .into_iter()
+        .map(|count| count as usize)
+        .collect::<Vec<_>>();
+
+    mining::benchmark::benchmark(
+        &algorithms,
+        &thread_counts,
+        &batch_sizes,
+        std::time::Duration::from_millis(duration_ms.into()),
+    )
+    .into_iter()
+    .map(|result| MiningBenchmarkResult {
+        hashes_per_second: result.hashes_per_second(),
+        algorithm: result.algorithm,
+        thread_count: result.thread_count as u32,
+        batch_size: result.batch_size,
+    })
+    .collect()
 }
 
 #[napi]
@@ -223,6 +357,68 @@ pub fn is_valid_public_address(hex_address: String) -> bool {
     PublicAddress::from_hex(&hex_address).is_ok()
 }
 
+#[napi]
+pub enum Bech32KeyType {
+    PublicAddress,
+    ViewKey,
+    IncomingViewKey,
+    SpendingKey,
+}
+
+/// Encode a hex address or key as a Bech32m string for the given network id.
+#[napi]
+pub fn encode_bech32(key_type: Bech32KeyType, hex_key: String, network_id: u32) -> Result<String> {
+    let network = Network::from_id(network_id).map_err(to_napi_err)?;
+
+    let encoded = match key_type {
+        Bech32KeyType::PublicAddress => PublicAddress::from_hex(&hex_key)
+            .map_err(to_napi_err)?
+            .to_bech32(network),
+        Bech32KeyType::ViewKey => ViewKey::from_hex(&hex_key)
+            .map_err(to_napi_err)?
+            .to_bech32(network),
+        Bech32KeyType::IncomingViewKey => IncomingViewKey::from_hex(&hex_key)
+            .map_err(to_napi_err)?
+            .to_bech32(network),
+        Bech32KeyType::SpendingKey => SaplingKey::from_hex(&hex_key)
+            .map_err(to_napi_err)?
+            .to_bech32(network),
+    };
+
+    Ok(encoded)
+}
+
+/// Decode a Bech32m address or key for the given network id, returning it as
+/// hex. Fails if the string is for a different type of key or network.
+#[napi]
+pub fn decode_bech32(key_type: Bech32KeyType, value: String, network_id: u32) -> Result<String> {
+    let network = Network::from_id(network_id).map_err(to_napi_err)?;
+
+    let hex_key = match key_type {
+        Bech32KeyType::PublicAddress => PublicAddress::from_bech32(&value, network)
+            .map_err(to_napi_err)?
+            .hex_public_address(),
+        Bech32KeyType::ViewKey => ViewKey::from_bech32(&value, network)
+            .map_err(to_napi_err)?
+            .hex_key(),
+        Bech32KeyType::IncomingViewKey => IncomingViewKey::from_bech32(&value, network)
+            .map_err(to_napi_err)?
+            .hex_key(),
+        Bech32KeyType::SpendingKey => SaplingKey::from_bech32(&value, network)
+            .map_err(to_napi_err)?
+            .hex_spending_key(),
+    };
+
+    Ok(hex_key)
+}
+
+#[napi]
+pub fn is_valid_bech32_public_address(address: String, network_id: u32) -> bool {
+    Network::from_id(network_id)
+        .and_then(|network| PublicAddress::from_bech32(&address, network))
+        .is_ok()
+}
+
 #[napi]
 pub struct CpuCount {
     /// Estimate of the number of threads that can run simultaneously on the system. This is
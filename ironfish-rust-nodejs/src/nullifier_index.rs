@@ -0,0 +1,142 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use ironfish::nullifier_index::{Nullifier, NullifierIndex, SpendStatus};
+use ironfish::transaction::Transaction;
+use ironfish::ViewKey;
+use napi::bindgen_prelude::*;
+use napi::JsBuffer;
+use napi_derive::napi;
+
+use crate::structs::NativeNote;
+use crate::to_napi_err;
+
+#[napi]
+pub enum NullifierStatus {
+    /// The nullifier does not belong to a note of the account
+    Unknown,
+    /// The note was unspent, and is now spent
+    Spent,
+    /// The note was already spent
+    DoubleSpend,
+}
+
+#[napi(object)]
+pub struct NullifierSpend {
+    pub status: NullifierStatus,
+    /// Position of the spent note, unless the status is `Unknown`
+    pub position: Option<i64>,
+}
+
+impl From<SpendStatus> for NullifierSpend {
+    fn from(status: SpendStatus) -> Self {
+        match status {
+            SpendStatus::Unknown => NullifierSpend {
+                status: NullifierStatus::Unknown,
+                position: None,
+            },
+            SpendStatus::Spent(position) => NullifierSpend {
+                status: NullifierStatus::Spent,
+                position: Some(position as i64),
+            },
+            SpendStatus::DoubleSpend(position) => NullifierSpend {
+                status: NullifierStatus::DoubleSpend,
+                position: Some(position as i64),
+            },
+        }
+    }
+}
+
+fn to_nullifier(js_bytes: JsBuffer) -> Result<Nullifier> {
+    let bytes = js_bytes.into_value()?;
+    let nullifier = bytes
+        .as_ref()
+        .try_into()
+        .map_err(|_| to_napi_err("Nullifier must be 32 bytes"))?;
+
+    Ok(Nullifier(nullifier))
+}
+
+#[napi(js_name = "NullifierIndex")]
+pub struct NativeNullifierIndex {
+    inner: NullifierIndex,
+}
+
+#[napi]
+impl NativeNullifierIndex {
+    #[napi(constructor)]
+    pub fn new(view_key: String) -> Result<Self> {
+        let view_key = ViewKey::from_hex(&view_key).map_err(to_napi_err)?;
+
+        Ok(Self {
+            inner: NullifierIndex::new(view_key),
+        })
+    }
+
+    /// Add an unspent note at the given position in the note commitment tree,
+    /// returning its nullifier.
+    #[napi]
+    pub fn add_note(&mut self, note: &NativeNote, position: BigInt) -> Buffer {
+        let nullifier = self.inner.add_note(note.note.clone(), position.get_u64().1);
+
+        Buffer::from(&nullifier.0[..])
+    }
+
+    /// Remove the note at the given position, returning whether it was found.
+    #[napi]
+    pub fn remove_note(&mut self, position: BigInt) -> bool {
+        self.inner.remove_note(position.get_u64().1).is_some()
+    }
+
+    #[napi]
+    pub fn mark_spent(&mut self, nullifier: JsBuffer) -> Result<NullifierSpend> {
+        let nullifier = to_nullifier(nullifier)?;
+
+        Ok(self.inner.mark_spent(&nullifier).into())
+    }
+
+    /// Mark the note with the given nullifier as unspent, returning its
+    /// position if it was spent.
+    #[napi]
+    pub fn mark_unspent(&mut self, nullifier: JsBuffer) -> Result<Option<i64>> {
+        let nullifier = to_nullifier(nullifier)?;
+
+        Ok(self
+            .inner
+            .mark_unspent(&nullifier)
+            .map(|position| position as i64))
+    }
+
+    /// Mark the notes spent by a serialized transaction as spent, returning
+    /// the status of each spend.
+    #[napi]
+    pub fn mark_transaction_spent(
+        &mut self,
+        transaction_bytes: JsBuffer,
+    ) -> Result<Vec<NullifierSpend>> {
+        let bytes = transaction_bytes.into_value()?;
+        let transaction = Transaction::read(bytes.as_ref()).map_err(to_napi_err)?;
+
+        Ok(self
+            .inner
+            .mark_transaction_spent(&transaction)
+            .into_iter()
+            .map(NullifierSpend::from)
+            .collect())
+    }
+
+    /// Whether the note with the given nullifier is spent, or `null` if it is
+    /// not a note of the account.
+    #[napi]
+    pub fn is_spent(&self, nullifier: JsBuffer) -> Result<Option<bool>> {
+        let nullifier = to_nullifier(nullifier)?;
+
+        Ok(self.inner.is_spent(&nullifier))
+    }
+
+    #[napi(getter)]
+    pub fn size(&self) -> u32 {
+        self.inner.len() as u32
+    }
+}
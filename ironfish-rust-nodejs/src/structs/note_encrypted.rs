@@ -6,11 +6,13 @@ use crate::to_napi_err;
 use ironfish::merkle_note::NOTE_ENCRYPTION_KEY_SIZE;
 use ironfish::note::ENCRYPTED_NOTE_SIZE;
 use ironfish::note::PLAINTEXT_NOTE_SIZE;
+use ironfish::note_scanner::NoteMatch;
 use ironfish::serializing::aead::MAC_SIZE;
 use ironfish::IncomingViewKey;
 use ironfish::MerkleNote;
 use ironfish::MerkleNoteHash;
 use ironfish::Note;
+use ironfish::NoteScanner;
 use ironfish::OutgoingViewKey;
 use napi::bindgen_prelude::*;
 use napi::JsBuffer;
@@ -162,3 +164,60 @@ impl NativeNoteEncrypted {
         decrypted_note_to_buffer(decrypted_note).map_err(to_napi_err)
     }
 }
+
+#[napi(object)]
+pub struct DecryptedNoteMatch {
+    pub note_index: u32,
+    pub key_index: u32,
+    pub note: Buffer,
+}
+
+#[napi(object)]
+pub struct ScannedNotes {
+    /// Notes decrypted with one of the incoming view keys
+    pub received: Vec<DecryptedNoteMatch>,
+    /// Notes decrypted with one of the outgoing view keys
+    pub sent: Vec<DecryptedNoteMatch>,
+}
+
+fn note_match_to_object(note_match: NoteMatch) -> Result<DecryptedNoteMatch> {
+    let mut buf = [0u8; PLAINTEXT_NOTE_SIZE];
+    note_match.note.write(&mut buf[..]).map_err(to_napi_err)?;
+
+    Ok(DecryptedNoteMatch {
+        note_index: note_match.note_index as u32,
+        key_index: note_match.key_index as u32,
+        note: Buffer::from(&buf[..]),
+    })
+}
+
+/// Attempt to decrypt all the encrypted notes of a block with all the given
+/// view keys at once. The indexes in the returned matches refer to the
+/// positions of the note and of the key in the arrays passed in.
+#[napi]
+pub fn scan_notes(
+    serialized_notes: Vec<JsBuffer>,
+    incoming_view_keys: Vec<JsBuffer>,
+    outgoing_view_keys: Option<Vec<JsBuffer>>,
+) -> Result<ScannedNotes> {
+    let notes = try_map(serialized_notes, |note| {
+        MerkleNote::read(&*note.into_value()?).map_err(to_napi_err)
+    })?;
+    let incoming_view_keys = try_map(incoming_view_keys, |incoming_view_key| {
+        IncomingViewKey::read(&*incoming_view_key.into_value()?).map_err(to_napi_err)
+    })?;
+    let outgoing_view_keys = try_map(
+        outgoing_view_keys.unwrap_or_default(),
+        |outgoing_view_key| {
+            OutgoingViewKey::read(&*outgoing_view_key.into_value()?).map_err(to_napi_err)
+        },
+    )?;
+
+    let scanner = NoteScanner::new(incoming_view_keys, outgoing_view_keys);
+    let result = scanner.scan(&notes);
+
+    Ok(ScannedNotes {
+        received: try_map(result.received, note_match_to_object)?,
+        sent: try_map(result.sent, note_match_to_object)?,
+    })
+}
@@ -17,12 +17,13 @@ use ironfish::serializing::fr::FrSerializable;
 use ironfish::serializing::hex_to_vec_bytes;
 use ironfish::transaction::unsigned::UnsignedTransaction;
 use ironfish::transaction::{
-    batch_verify_transactions, TransactionVersion, TRANSACTION_EXPIRATION_SIZE,
-    TRANSACTION_FEE_SIZE, TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE,
+    batch_verify_transactions, batch_verify_transactions_with_failures, TransactionVersion,
+    TRANSACTION_EXPIRATION_SIZE, TRANSACTION_FEE_SIZE, TRANSACTION_PUBLIC_KEY_SIZE,
+    TRANSACTION_SIGNATURE_SIZE,
 };
 use ironfish::{
-    MerkleNoteHash, OutgoingViewKey, ProposedTransaction, PublicAddress, SaplingKey, Transaction,
-    ViewKey,
+    errors::IronfishErrorKind, MerkleNoteHash, OutgoingViewKey, ProposedTransaction, PublicAddress,
+    SaplingKey, Transaction, ViewKey,
 };
 use ironfish_frost::dkg::round3::PublicKeyPackage;
 use ironfish_frost::signature_share::SignatureShare;
@@ -161,10 +162,7 @@ impl NativeTransactionPosted {
 
     #[napi]
     pub fn hash(&self) -> Result<Buffer> {
-        let hash = self
-            .transaction
-            .transaction_signature_hash()
-            .map_err(to_napi_err)?;
+        let hash = self.transaction.unsigned_hash().map_err(to_napi_err)?;
 
         Ok(Buffer::from(hash.as_ref()))
     }
@@ -364,6 +362,26 @@ impl NativeTransaction {
     pub fn set_expiration(&mut self, sequence: u32) -> Undefined {
         self.transaction.set_expiration(sequence);
     }
+
+    /// Serialize the transaction before any proof is created, so that it can
+    /// be posted elsewhere. The result contains the notes and witnesses of the
+    /// transaction and must be kept private.
+    #[napi]
+    pub fn serialize(&self) -> Result<Buffer> {
+        let mut vec: Vec<u8> = vec![];
+        self.transaction.write(&mut vec).map_err(to_napi_err)?;
+
+        Ok(Buffer::from(vec))
+    }
+
+    #[napi(factory)]
+    pub fn deserialize(js_bytes: JsBuffer) -> Result<Self> {
+        let bytes = js_bytes.into_value()?;
+
+        let transaction = ProposedTransaction::read(bytes.as_ref()).map_err(to_napi_err)?;
+
+        Ok(NativeTransaction { transaction })
+    }
 }
 
 #[napi]
@@ -381,6 +399,57 @@ pub fn verify_transactions(serialized_transactions: Vec<JsBuffer>) -> Result<boo
     Ok(batch_verify_transactions(transactions.iter()).is_ok())
 }
 
+#[napi(object)]
+pub struct TransactionVerificationFailure {
+    pub index: u32,
+    /// The kind of error, followed by where in the transaction it happened
+    /// when known, for example `InvalidData at spends[3].nullifier`
+    pub error: String,
+}
+
+fn failure_message(kind: &IronfishErrorKind, context: Option<String>) -> String {
+    match context {
+        Some(context) => format!("{:?} at {}", kind, context),
+        None => format!("{:?}", kind),
+    }
+}
+
+/// Verify a batch of transactions, returning the index and error of every
+/// transaction that failed verification. An empty array means that all
+/// transactions are valid.
+#[napi]
+pub fn verify_transactions_with_failures(
+    serialized_transactions: Vec<JsBuffer>,
+) -> Result<Vec<TransactionVerificationFailure>> {
+    let mut failures = vec![];
+    let mut transactions = vec![];
+    let mut indexes = vec![];
+
+    for (index, tx_bytes) in serialized_transactions.into_iter().enumerate() {
+        let buf = tx_bytes.into_value()?;
+        match Transaction::read(buf.as_ref()) {
+            Ok(tx) => {
+                transactions.push(tx);
+                indexes.push(index as u32);
+            }
+            Err(e) => failures.push(TransactionVerificationFailure {
+                index: index as u32,
+                error: failure_message(&e.kind, e.context_path()),
+            }),
+        }
+    }
+
+    for failure in batch_verify_transactions_with_failures(transactions.iter()) {
+        failures.push(TransactionVerificationFailure {
+            index: indexes[failure.index],
+            error: failure_message(&failure.kind, failure.context),
+        });
+    }
+
+    failures.sort_by_key(|failure| failure.index);
+    Ok(failures)
+}
+
 #[napi(js_name = "UnsignedTransaction")]
 pub struct NativeUnsignedTransaction {
     pub(crate) transaction: UnsignedTransaction,
@@ -419,10 +488,7 @@ impl NativeUnsignedTransaction {
 
     #[napi]
     pub fn hash(&self) -> Result<Buffer> {
-        let hash = self
-            .transaction
-            .transaction_signature_hash()
-            .map_err(to_napi_err)?;
+        let hash = self.transaction.hash().map_err(to_napi_err)?;
 
         Ok(Buffer::from(hash.as_ref()))
     }
@@ -145,5 +145,8 @@ fn prepare_sapling_params() {
 }
 
 fn main() {
-    prepare_sapling_params();
+    // The parameters are only needed when they get embedded in the binary
+    if env::var_os("CARGO_FEATURE_EMBEDDED_PARAMS").is_some() {
+        prepare_sapling_params();
+    }
 }
@@ -13,8 +13,12 @@ use std::string;
 #[derive(Debug)]
 pub struct IronfishError {
     pub kind: IronfishErrorKind,
-    pub source: Option<Box<dyn Error>>,
+    pub source: Option<Box<dyn Error + Send + Sync>>,
     pub backtrace: Backtrace,
+    /// Where the error happened, from the innermost to the outermost
+    /// location, for example `["nullifier", "spends[3]"]`. See
+    /// [`IronfishError::context_path`].
+    pub context: Vec<String>,
 }
 
 /// Error type to handle all errors within the code and dependency-raised
@@ -36,24 +40,34 @@ pub enum IronfishErrorKind {
     HexError,
     IllegalValue,
     InconsistentWitness,
+    InsufficientFunds,
     InvalidAssetIdentifier,
     InvalidAuthorizingKey,
     InvalidBalance,
+    InvalidBech32,
+    InvalidBech32Prefix,
     InvalidCommitment,
     InvalidData,
     InvalidDecryptionKey,
+    InvalidDerivationPath,
     InvalidDiversificationPoint,
     InvalidEntropy,
+    InvalidFishHashDataset,
     InvalidFr,
     InvalidLanguageEncoding,
+    InvalidMemo,
     InvalidMinersFeeTransaction,
+    InvalidMiningAlgorithm,
     InvalidMintProof,
     InvalidMintSignature,
     InvalidMnemonicString,
+    InvalidNetworkId,
     InvalidNonceLength,
     InvalidNullifierDerivingKey,
     InvalidOutputProof,
+    InvalidParameters,
     InvalidPaymentAddress,
+    InvalidPaymentRequest,
     InvalidPublicAddress,
     InvalidSecret,
     InvalidRandomizer,
@@ -63,12 +77,16 @@ pub enum IronfishErrorKind {
     InvalidSpendSignature,
     InvalidTransaction,
     InvalidTransactionVersion,
+    InvalidTreePosition,
+    InvalidTreeSize,
     InvalidViewingKey,
     InvalidWord,
     Io,
     IsSmallOrder,
     RandomnessError,
     RoundTwoSigningFailure,
+    SaplingAlreadyInitialized,
+    TooManySpends,
     TryFromInt,
     Utf8,
 }
@@ -79,19 +97,74 @@ impl IronfishError {
             kind,
             source: None,
             backtrace: Backtrace::capture(),
+            context: vec![],
         }
     }
 
     pub fn new_with_source<E>(kind: IronfishErrorKind, source: E) -> Self
     where
-        E: Into<Box<dyn Error>>,
+        E: Into<Box<dyn Error + Send + Sync>>,
     {
         Self {
             kind,
             source: Some(source.into()),
             backtrace: Backtrace::capture(),
+            context: vec![],
         }
     }
+
+    /// Attach the location of the error within the enclosing value, such as
+    /// a field name or an index. Locations are added as the error propagates
+    /// outward, so each one is relative to the next.
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context.push(context.into());
+        self
+    }
+
+    /// The path to where the error happened, outermost location first, for
+    /// example `spends[3].nullifier`.
+    pub fn context_path(&self) -> Option<String> {
+        if self.context.is_empty() {
+            return None;
+        }
+
+        let mut path = String::new();
+        for context in self.context.iter().rev() {
+            if !path.is_empty() && !context.starts_with('[') {
+                path.push('.');
+            }
+            path.push_str(context);
+        }
+
+        Some(path)
+    }
+}
+
+/// Attach context to the error of a [`Result`], converting it to an
+/// [`IronfishError`].
+pub trait ErrorContext<T> {
+    /// Attach a static location, such as a field name.
+    fn context(self, context: &'static str) -> Result<T, IronfishError>;
+
+    /// Attach a location built only if there is an error, such as an index.
+    fn with_context<C, F>(self, context: F) -> Result<T, IronfishError>
+    where
+        C: Into<String>,
+        F: FnOnce() -> C;
+}
+
+impl<T, E: Into<IronfishError>> ErrorContext<T> for Result<T, E> {
+    fn context(self, context: &'static str) -> Result<T, IronfishError> {
+        self.map_err(|e| e.into().with_context(context))
+    }
+
+    fn with_context<C, F>(self, context: F) -> Result<T, IronfishError>
+    where
+        C: Into<String>,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|e| e.into().with_context(context()))
+    }
 }
 
 impl Error for IronfishError {}
@@ -100,6 +173,9 @@ impl fmt::Display for IronfishError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let has_backtrace = self.backtrace.status() == BacktraceStatus::Captured;
         write!(f, "{:?}", self.kind)?;
+        if let Some(path) = self.context_path() {
+            write!(f, " at {}", path)?;
+        }
         if let Some(source) = &self.source {
             write!(f, "\nCaused by: \n{}", source)?;
         }
@@ -158,3 +234,30 @@ impl From<ironfish_zkp::hex::HexError> for IronfishError {
         IronfishError::new_with_source(IronfishErrorKind::HexError, e)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ErrorContext, IronfishError, IronfishErrorKind};
+
+    #[test]
+    fn test_context_path() {
+        let error = IronfishError::new(IronfishErrorKind::InvalidData);
+        assert_eq!(error.context_path(), None);
+
+        let result: Result<(), IronfishError> = Err(error);
+        let error = result
+            .context("nullifier")
+            .with_context(|| format!("spends[{}]", 3))
+            .unwrap_err();
+        assert_eq!(error.kind, IronfishErrorKind::InvalidData);
+        assert_eq!(error.context_path().unwrap(), "spends[3].nullifier");
+        assert!(error
+            .to_string()
+            .starts_with("InvalidData at spends[3].nullifier"));
+
+        let result: Result<(), std::io::Error> = Err(std::io::ErrorKind::UnexpectedEof.into());
+        let error = result.context("proof").unwrap_err();
+        assert_eq!(error.kind, IronfishErrorKind::Io);
+        assert_eq!(error.context_path().unwrap(), "proof");
+    }
+}
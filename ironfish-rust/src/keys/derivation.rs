@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Hierarchical deterministic derivation of spending keys, in the style of
+//! [ZIP-32](https://zips.z.cash/zip-0032).
+//!
+//! Only hardened derivation is supported: a child key can be derived from its
+//! parent spending key, but not from any of the parent's public keys.
+
+use super::{SaplingKey, SPEND_KEY_SIZE};
+use crate::errors::{IronfishError, IronfishErrorKind};
+use blake2b_simd::Params as Blake2b;
+use std::{fmt, str::FromStr};
+
+const CHAIN_CODE_PERSONALIZATION: &[u8; 16] = b"Iron Fish ChnCod";
+const CHILD_KEY_PERSONALIZATION: &[u8; 16] = b"Iron Fish ChldKy";
+
+/// Domain separator for hardened child derivation, as used by ZIP-32.
+const HARDENED_CHILD_DOMAIN: u8 = 0x11;
+
+/// Indexes at or above this value are hardened.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// A sequence of hardened child indexes, such as `m/44'/1338'/0'`.
+///
+/// Indexes are stored without the hardened offset. Components may be marked
+/// as hardened with either `'` or `h`; components without a marker are
+/// rejected, since only hardened derivation is supported.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DerivationPath {
+    indexes: Vec<u32>,
+}
+
+impl DerivationPath {
+    pub fn new(indexes: Vec<u32>) -> Result<Self, IronfishError> {
+        if indexes.iter().any(|index| *index >= HARDENED_OFFSET) {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidDerivationPath));
+        }
+
+        Ok(DerivationPath { indexes })
+    }
+
+    pub fn indexes(&self) -> &[u32] {
+        &self.indexes
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = IronfishError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidDerivationPath));
+        }
+
+        let indexes = components
+            .map(|component| {
+                let index = component
+                    .strip_suffix('\'')
+                    .or_else(|| component.strip_suffix('h'))
+                    .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidDerivationPath))?;
+
+                // Only plain decimal digits are accepted, so that each path has
+                // a single representation
+                if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(IronfishError::new(IronfishErrorKind::InvalidDerivationPath));
+                }
+
+                index.parse::<u32>().map_err(|e| {
+                    IronfishError::new_with_source(IronfishErrorKind::InvalidDerivationPath, e)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        DerivationPath::new(indexes)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.indexes {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl SaplingKey {
+    /// Derive the hardened child key at `index` from this key.
+    ///
+    /// As in ZIP-32, the child spending key is computed with a keyed hash of
+    /// the parent spending key and the index, where the key is the chain code
+    /// of the parent. Iron Fish keys do not carry a separate chain code, so it
+    /// is itself derived from the spending key.
+    pub fn derive_child(&self, index: u32) -> Result<SaplingKey, IronfishError> {
+        if index >= HARDENED_OFFSET {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidDerivationPath));
+        }
+
+        let chain_code = Blake2b::new()
+            .hash_length(32)
+            .personal(CHAIN_CODE_PERSONALIZATION)
+            .hash(&self.spending_key);
+
+        let mut hasher = Blake2b::new()
+            .hash_length(SPEND_KEY_SIZE)
+            .key(chain_code.as_bytes())
+            .personal(CHILD_KEY_PERSONALIZATION)
+            .to_state();
+        hasher.update(&[HARDENED_CHILD_DOMAIN]);
+        hasher.update(&self.spending_key);
+        hasher.update(&(index | HARDENED_OFFSET).to_le_bytes());
+
+        let mut spending_key = [0; SPEND_KEY_SIZE];
+        spending_key.copy_from_slice(hasher.finalize().as_bytes());
+        SaplingKey::new(spending_key)
+    }
+
+    /// Derive the key at `path`, treating this key as the root `m`.
+    pub fn derive_path(&self, path: &DerivationPath) -> Result<SaplingKey, IronfishError> {
+        path.indexes()
+            .iter()
+            .try_fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+}
@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Diversified addresses allow a single account to hand out any number of
+//! public addresses that cannot be linked to each other.
+//!
+//! The spend circuit requires the owner of a note to be `ivk * G`, where
+//! `ivk` is the hash of the authorizing key `ak` and the nullifier deriving
+//! key `nk`. Each diversified address therefore uses its own authorizing key
+//! `ak + t * G`, where the tweak `t` is derived from the view key and the
+//! diversifier index. The matching spend authorizing key is `ask + t`, so
+//! notes sent to any diversified address remain spendable by the account, and
+//! share its nullifier deriving key.
+//!
+//! The tweak can only be computed from the full [`ViewKey`]: an
+//! [`IncomingViewKey`] is enough to decrypt the notes sent to one diversified
+//! address, but not to derive the others.
+
+use super::{IncomingViewKey, PublicAddress, SaplingKey, ViewKey};
+use crate::errors::IronfishError;
+use blake2b_simd::Params as Blake2b;
+use group::GroupEncoding;
+use ironfish_jubjub::SubgroupPoint;
+use ironfish_zkp::constants::SPENDING_KEY_GENERATOR;
+
+const DIVERSIFIER_TWEAK_PERSONALIZATION: &[u8; 16] = b"Iron Fish Divers";
+
+/// The diversifier index of the default address of an account, which is the
+/// address returned by [`SaplingKey::public_address`].
+pub const DEFAULT_DIVERSIFIER_INDEX: u64 = 0;
+
+impl ViewKey {
+    /// The scalar added to the authorizing key for the given diversifier
+    /// index. This is zero for the default address.
+    pub(crate) fn diversifier_tweak(&self, diversifier_index: u64) -> ironfish_jubjub::Fr {
+        if diversifier_index == DEFAULT_DIVERSIFIER_INDEX {
+            return ironfish_jubjub::Fr::zero();
+        }
+
+        let mut hasher = Blake2b::new()
+            .hash_length(64)
+            .personal(DIVERSIFIER_TWEAK_PERSONALIZATION)
+            .to_state();
+        hasher.update(&self.authorizing_key.to_bytes());
+        hasher.update(&self.nullifier_deriving_key.to_bytes());
+        hasher.update(&diversifier_index.to_le_bytes());
+
+        let mut hash_result = [0; 64];
+        hash_result.copy_from_slice(hasher.finalize().as_bytes());
+        ironfish_jubjub::Fr::from_bytes_wide(&hash_result)
+    }
+
+    /// The authorizing key of the address with the given diversifier index,
+    /// used in place of `ak` when spending notes sent to that address.
+    pub(crate) fn diversified_authorizing_key(&self, diversifier_index: u64) -> SubgroupPoint {
+        self.authorizing_key + *SPENDING_KEY_GENERATOR * self.diversifier_tweak(diversifier_index)
+    }
+
+    /// The incoming view key that decrypts the notes sent to the address with
+    /// the given diversifier index.
+    pub fn diversified_incoming_view_key(
+        &self,
+        diversifier_index: u64,
+    ) -> Result<IncomingViewKey, IronfishError> {
+        Ok(IncomingViewKey {
+            view_key: SaplingKey::hash_viewing_key(
+                &self.diversified_authorizing_key(diversifier_index),
+                &self.nullifier_deriving_key,
+            )?,
+        })
+    }
+
+    /// The public address with the given diversifier index.
+    pub fn diversified_public_address(
+        &self,
+        diversifier_index: u64,
+    ) -> Result<PublicAddress, IronfishError> {
+        Ok(self
+            .diversified_incoming_view_key(diversifier_index)?
+            .public_address())
+    }
+
+    /// Find the diversifier index of `address`, for example the owner of a
+    /// decrypted note, among the first `search_limit` indexes. Returns `None`
+    /// if the address does not belong to this account, or has a higher index.
+    pub fn find_diversifier_index(
+        &self,
+        address: &PublicAddress,
+        search_limit: u64,
+    ) -> Result<Option<u64>, IronfishError> {
+        for diversifier_index in 0..search_limit {
+            if self.diversified_public_address(diversifier_index)? == *address {
+                return Ok(Some(diversifier_index));
+            }
+        }
+
+        Ok(None)
+    }
+}
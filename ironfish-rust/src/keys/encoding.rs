@@ -0,0 +1,195 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Human-readable [Bech32m](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki)
+//! encoding of addresses and keys.
+//!
+//! Unlike hex, the encoding includes a checksum that catches typos, and a
+//! human-readable prefix that identifies both the kind of key and the network
+//! it is meant for, so that a view key cannot be mistaken for an address:
+//!
+//! | Kind               | Mainnet    | Testnet        | Devnet        |
+//! |--------------------|------------|----------------|---------------|
+//! | Public address     | `ifaddr`   | `ifaddrtest`   | `ifaddrdev`   |
+//! | View key           | `ifview`   | `ifviewtest`   | `ifviewdev`   |
+//! | Incoming view key  | `ifivk`    | `ifivktest`    | `ifivkdev`    |
+//! | Spending key       | `ifsecret` | `ifsecrettest` | `ifsecretdev` |
+
+use super::{IncomingViewKey, PublicAddress, SaplingKey, ViewKey, SPEND_KEY_SIZE};
+use crate::errors::{IronfishError, IronfishErrorKind};
+use bech32::{FromBase32, ToBase32, Variant};
+
+/// The network that an encoded address or key is meant for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl Network {
+    /// Get the network from its numeric id, as used in the network
+    /// definitions of the node.
+    pub fn from_id(id: u32) -> Result<Self, IronfishError> {
+        match id {
+            0 => Ok(Network::Testnet),
+            1 => Ok(Network::Mainnet),
+            2 => Ok(Network::Devnet),
+            _ => Err(IronfishError::new(IronfishErrorKind::InvalidNetworkId)),
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        match self {
+            Network::Testnet => 0,
+            Network::Mainnet => 1,
+            Network::Devnet => 2,
+        }
+    }
+
+    fn hrp_suffix(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "",
+            Network::Testnet => "test",
+            Network::Devnet => "dev",
+        }
+    }
+
+    const ALL: [Network; 3] = [Network::Mainnet, Network::Testnet, Network::Devnet];
+}
+
+/// The kind of data in a Bech32m string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bech32Kind {
+    PublicAddress,
+    ViewKey,
+    IncomingViewKey,
+    SpendingKey,
+}
+
+impl Bech32Kind {
+    /// The human-readable part of the encoding of this kind of data on the
+    /// given network.
+    pub fn hrp(&self, network: Network) -> String {
+        let prefix = match self {
+            Bech32Kind::PublicAddress => "ifaddr",
+            Bech32Kind::ViewKey => "ifview",
+            Bech32Kind::IncomingViewKey => "ifivk",
+            Bech32Kind::SpendingKey => "ifsecret",
+        };
+
+        format!("{}{}", prefix, network.hrp_suffix())
+    }
+
+    const ALL: [Bech32Kind; 4] = [
+        Bech32Kind::PublicAddress,
+        Bech32Kind::ViewKey,
+        Bech32Kind::IncomingViewKey,
+        Bech32Kind::SpendingKey,
+    ];
+}
+
+/// Encode `bytes` as a Bech32m string for the given kind and network.
+pub fn encode_bech32(kind: Bech32Kind, network: Network, bytes: &[u8]) -> String {
+    bech32::encode(&kind.hrp(network), bytes.to_base32(), Variant::Bech32m)
+        .expect("human-readable parts are valid")
+}
+
+/// Decode a Bech32m string, returning the kind of data and network that its
+/// prefix identifies, along with the data.
+pub fn decode_bech32(value: &str) -> Result<(Bech32Kind, Network, Vec<u8>), IronfishError> {
+    let (hrp, data, variant) = bech32::decode(value)
+        .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidBech32, e))?;
+
+    if variant != Variant::Bech32m {
+        return Err(IronfishError::new(IronfishErrorKind::InvalidBech32));
+    }
+
+    let (kind, network) = Bech32Kind::ALL
+        .iter()
+        .flat_map(|kind| Network::ALL.iter().map(move |network| (*kind, *network)))
+        .find(|(kind, network)| kind.hrp(*network) == hrp)
+        .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidBech32Prefix))?;
+
+    let bytes = Vec::<u8>::from_base32(&data)
+        .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidBech32, e))?;
+
+    Ok((kind, network, bytes))
+}
+
+/// Decode a Bech32m string of `N` bytes, checking that it was encoded for the
+/// expected kind and network.
+fn decode_bech32_exact<const N: usize>(
+    value: &str,
+    expected_kind: Bech32Kind,
+    expected_network: Network,
+) -> Result<[u8; N], IronfishError> {
+    let (kind, network, bytes) = decode_bech32(value)?;
+
+    if kind != expected_kind || network != expected_network {
+        return Err(IronfishError::new(IronfishErrorKind::InvalidBech32Prefix));
+    }
+
+    bytes
+        .try_into()
+        .map_err(|_| IronfishError::new(IronfishErrorKind::InvalidBech32))
+}
+
+impl PublicAddress {
+    /// Encode this address as a Bech32m string for the given network.
+    pub fn to_bech32(&self, network: Network) -> String {
+        encode_bech32(Bech32Kind::PublicAddress, network, &self.public_address())
+    }
+
+    /// Parse an address encoded with [`PublicAddress::to_bech32`].
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = decode_bech32_exact(value, Bech32Kind::PublicAddress, network)?;
+        Self::new(&bytes)
+    }
+}
+
+impl ViewKey {
+    /// Encode this view key as a Bech32m string for the given network.
+    pub fn to_bech32(&self, network: Network) -> String {
+        encode_bech32(Bech32Kind::ViewKey, network, &self.to_bytes())
+    }
+
+    /// Parse a view key encoded with [`ViewKey::to_bech32`].
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes = decode_bech32_exact(value, Bech32Kind::ViewKey, network)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl IncomingViewKey {
+    /// Encode this incoming view key as a Bech32m string for the given
+    /// network.
+    pub fn to_bech32(&self, network: Network) -> String {
+        encode_bech32(
+            Bech32Kind::IncomingViewKey,
+            network,
+            &self.view_key.to_bytes(),
+        )
+    }
+
+    /// Parse an incoming view key encoded with [`IncomingViewKey::to_bech32`].
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes: [u8; 32] = decode_bech32_exact(value, Bech32Kind::IncomingViewKey, network)?;
+        Self::read(&bytes[..])
+    }
+}
+
+impl SaplingKey {
+    /// Encode the spending key as a Bech32m string for the given network.
+    pub fn to_bech32(&self, network: Network) -> String {
+        encode_bech32(Bech32Kind::SpendingKey, network, &self.spending_key)
+    }
+
+    /// Parse a spending key encoded with [`SaplingKey::to_bech32`].
+    pub fn from_bech32(value: &str, network: Network) -> Result<Self, IronfishError> {
+        let bytes: [u8; SPEND_KEY_SIZE] =
+            decode_bech32_exact(value, Bech32Kind::SpendingKey, network)?;
+        Self::new(bytes)
+    }
+}
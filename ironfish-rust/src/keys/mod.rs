@@ -19,6 +19,12 @@ use rand::prelude::*;
 
 use std::io;
 
+mod derivation;
+pub use derivation::*;
+mod diversification;
+pub use diversification::*;
+mod encoding;
+pub use encoding::*;
 mod ephemeral;
 pub use ephemeral::EphemeralKeyPair;
 mod public_address;
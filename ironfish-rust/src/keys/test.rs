@@ -2,11 +2,21 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use crate::keys::{ephemeral::EphemeralKeyPair, PUBLIC_ADDRESS_SIZE};
+use crate::{
+    assets::asset_identifier::NATIVE_ASSET,
+    errors::IronfishErrorKind,
+    keys::{ephemeral::EphemeralKeyPair, PUBLIC_ADDRESS_SIZE},
+    note::Note,
+    MerkleNote,
+};
 
-use super::{shared_secret, PublicAddress, SaplingKey};
+use super::{
+    decode_bech32, encode_bech32, shared_secret, Bech32Kind, DerivationPath, IncomingViewKey,
+    Network, PublicAddress, SaplingKey, ViewKey, DEFAULT_DIVERSIFIER_INDEX,
+};
 use group::Curve;
 use ironfish_jubjub::ExtendedPoint;
+use ironfish_zkp::primitives::ValueCommitment;
 
 #[test]
 fn test_key_generation_and_construction() {
@@ -130,3 +140,259 @@ fn test_from_and_to_words() {
         SaplingKey::from_words(words, bip39::Language::English).expect("key should be created");
     assert_eq!(key.spending_key, key_bytes);
 }
+
+#[test]
+fn test_derive_child() {
+    let key =
+        SaplingKey::from_hex("d96dc74bbca05dffb14a5631024588364b0cc9f583b5c11908b6ea98a2b778f7")
+            .unwrap();
+
+    let child = key.derive_child(0).expect("child key should be derived");
+    assert_eq!(
+        child.hex_spending_key(),
+        "f32ff960fa6201e7c1a3808f4a7dcf75e8f718ca552eb357141d2c2423e4d94b"
+    );
+
+    // Derivation is deterministic, and distinct indexes give distinct keys
+    assert_eq!(
+        key.derive_child(0).unwrap().spending_key,
+        child.spending_key
+    );
+    assert_ne!(
+        key.derive_child(1).unwrap().spending_key,
+        child.spending_key
+    );
+
+    assert!(key.derive_child(super::HARDENED_OFFSET).is_err());
+}
+
+#[test]
+fn test_derive_path() {
+    let key =
+        SaplingKey::from_hex("d96dc74bbca05dffb14a5631024588364b0cc9f583b5c11908b6ea98a2b778f7")
+            .unwrap();
+
+    let path: DerivationPath = "m/44'/1338'/0'".parse().unwrap();
+    assert_eq!(path.indexes(), &[44, 1338, 0]);
+    assert_eq!(path.to_string(), "m/44'/1338'/0'");
+
+    let child = key.derive_path(&path).expect("child key should be derived");
+    assert_eq!(
+        child.hex_spending_key(),
+        "07afd33a7c9d7deb09adfd1b264c3b0e028f6b7503c20844a41398a63b352a6b"
+    );
+
+    let same_path: DerivationPath = "m/44h/1338h/0h".parse().unwrap();
+    assert_eq!(same_path, path);
+
+    let root: DerivationPath = "m".parse().unwrap();
+    assert_eq!(
+        key.derive_path(&root).unwrap().spending_key,
+        key.spending_key
+    );
+}
+
+#[test]
+fn test_derivation_path_invalid() {
+    for path in [
+        "",
+        "44'/0'",
+        "m/",
+        "m/44",
+        "m/44'/0",
+        "m/'",
+        "m/+1'",
+        "m/2147483648'",
+        "m/4294967296'",
+        "M/44'",
+    ] {
+        assert!(
+            path.parse::<DerivationPath>().is_err(),
+            "{} should not parse",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_diversified_addresses() {
+    let key = SaplingKey::generate_key();
+    let view_key = key.view_key();
+
+    assert_eq!(
+        view_key
+            .diversified_public_address(DEFAULT_DIVERSIFIER_INDEX)
+            .unwrap(),
+        key.public_address()
+    );
+
+    let first = view_key.diversified_public_address(1).unwrap();
+    let second = view_key.diversified_public_address(2).unwrap();
+    assert_ne!(first, key.public_address());
+    assert_ne!(first, second);
+    assert_eq!(view_key.diversified_public_address(1).unwrap(), first);
+
+    assert_eq!(
+        view_key
+            .diversified_incoming_view_key(2)
+            .unwrap()
+            .public_address(),
+        second
+    );
+
+    assert_eq!(
+        view_key.find_diversifier_index(&second, 10).unwrap(),
+        Some(2)
+    );
+    assert_eq!(view_key.find_diversifier_index(&second, 2).unwrap(), None);
+
+    let other_key = SaplingKey::generate_key();
+    assert_eq!(
+        other_key
+            .view_key()
+            .find_diversifier_index(&second, 10)
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_diversified_address_trial_decryption() {
+    let key = SaplingKey::generate_key();
+    let sender_key = SaplingKey::generate_key();
+    let diversifier_index = 7;
+    let address = key
+        .view_key()
+        .diversified_public_address(diversifier_index)
+        .unwrap();
+
+    let note = Note::new(address, 42, "", NATIVE_ASSET, sender_key.public_address());
+    let value_commitment = ValueCommitment::new(note.value(), note.asset_generator());
+    let merkle_note = MerkleNote::new(
+        sender_key.outgoing_view_key(),
+        &note,
+        &value_commitment,
+        &EphemeralKeyPair::new(),
+    );
+
+    assert!(merkle_note
+        .decrypt_note_for_owner(key.incoming_view_key())
+        .is_err());
+
+    let ivk = key
+        .view_key()
+        .diversified_incoming_view_key(diversifier_index)
+        .unwrap();
+    let decrypted_note = merkle_note
+        .decrypt_note_for_owner(&ivk)
+        .expect("should be able to decrypt note for diversified address");
+    assert_eq!(decrypted_note.value(), 42);
+    assert_eq!(decrypted_note.owner(), address);
+
+    assert_eq!(
+        key.view_key()
+            .find_diversifier_index(&decrypted_note.owner(), 10)
+            .unwrap(),
+        Some(diversifier_index)
+    );
+}
+
+#[test]
+fn test_bech32_spending_key() {
+    let key =
+        SaplingKey::from_hex("d96dc74bbca05dffb14a5631024588364b0cc9f583b5c11908b6ea98a2b778f7")
+            .unwrap();
+
+    let encoded = key.to_bech32(Network::Mainnet);
+    assert_eq!(
+        encoded,
+        "ifsecret1m9kuwjau5pwllv222ccsy3vgxe9sej04sw6uzxggkm4f3g4h0rmsrcrvax"
+    );
+    assert_eq!(
+        key.to_bech32(Network::Testnet),
+        "ifsecrettest1m9kuwjau5pwllv222ccsy3vgxe9sej04sw6uzxggkm4f3g4h0rms7dsh8f"
+    );
+
+    let decoded = SaplingKey::from_bech32(&encoded, Network::Mainnet).unwrap();
+    assert_eq!(decoded.spending_key, key.spending_key);
+
+    // Upper case strings are valid Bech32m
+    let decoded = SaplingKey::from_bech32(&encoded.to_uppercase(), Network::Mainnet).unwrap();
+    assert_eq!(decoded.spending_key, key.spending_key);
+}
+
+#[test]
+fn test_bech32_round_trip() {
+    let key = SaplingKey::generate_key();
+
+    for network in [Network::Mainnet, Network::Testnet, Network::Devnet] {
+        let address = key.public_address().to_bech32(network);
+        assert_eq!(
+            PublicAddress::from_bech32(&address, network).unwrap(),
+            key.public_address()
+        );
+
+        let view_key = key.view_key().to_bech32(network);
+        assert_eq!(
+            ViewKey::from_bech32(&view_key, network).unwrap().to_bytes(),
+            key.view_key().to_bytes()
+        );
+
+        let incoming_view_key = key.incoming_view_key().to_bech32(network);
+        assert_eq!(
+            IncomingViewKey::from_bech32(&incoming_view_key, network)
+                .unwrap()
+                .view_key,
+            key.incoming_view_key().view_key
+        );
+
+        let (kind, decoded_network, bytes) = decode_bech32(&view_key).unwrap();
+        assert_eq!(kind, Bech32Kind::ViewKey);
+        assert_eq!(decoded_network, network);
+        assert_eq!(bytes, key.view_key().to_bytes());
+    }
+}
+
+#[test]
+fn test_bech32_invalid() {
+    let key = SaplingKey::generate_key();
+    let address = key.public_address().to_bech32(Network::Mainnet);
+    let view_key = key.view_key().to_bech32(Network::Mainnet);
+
+    // Wrong kind of key
+    let err = PublicAddress::from_bech32(&view_key, Network::Mainnet).unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32Prefix);
+
+    // Wrong network
+    let err = PublicAddress::from_bech32(&address, Network::Testnet).unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32Prefix);
+
+    // Typo
+    let mut typo = address.clone().into_bytes();
+    let last = typo.len() - 1;
+    typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+    let err = PublicAddress::from_bech32(&String::from_utf8(typo).unwrap(), Network::Mainnet)
+        .unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32);
+
+    // Hex is not accepted
+    let err =
+        PublicAddress::from_bech32(&key.public_address().hex_public_address(), Network::Mainnet)
+            .unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32);
+
+    // Original Bech32 checksums are rejected
+    let bech32 = bech32::encode(
+        "ifaddr",
+        bech32::ToBase32::to_base32(&key.public_address().public_address()),
+        bech32::Variant::Bech32,
+    )
+    .unwrap();
+    let err = PublicAddress::from_bech32(&bech32, Network::Mainnet).unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32);
+
+    // Wrong payload length
+    let truncated = encode_bech32(Bech32Kind::PublicAddress, Network::Mainnet, &[0; 31]);
+    let err = PublicAddress::from_bech32(&truncated, Network::Mainnet).unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InvalidBech32);
+}
@@ -121,7 +121,12 @@ impl ViewKey {
     /// Load a key from a string of hexadecimal digits
     pub fn from_hex(value: &str) -> Result<Self, IronfishError> {
         let bytes: [u8; 64] = hex_to_bytes(value)?;
+        Self::from_bytes(&bytes)
+    }
 
+    /// Load a key from its 64 byte representation, as returned by
+    /// [`ViewKey::to_bytes`]
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self, IronfishError> {
         let mut authorizing_key_bytes = [0; 32];
         let mut nullifier_deriving_key_bytes = [0; 32];
 
@@ -215,6 +220,45 @@ impl OutgoingViewKey {
     }
 }
 
+/// All the keys that give full view access to an account, without the
+/// ability to spend from it.
+#[derive(Clone)]
+pub struct ViewKeys {
+    pub view_key: ViewKey,
+    pub incoming_view_key: IncomingViewKey,
+    pub outgoing_view_key: OutgoingViewKey,
+}
+
+impl ViewKeys {
+    pub fn new(
+        view_key: ViewKey,
+        outgoing_view_key: OutgoingViewKey,
+    ) -> Result<Self, IronfishError> {
+        let incoming_view_key = IncomingViewKey {
+            view_key: SaplingKey::hash_viewing_key(
+                &view_key.authorizing_key,
+                &view_key.nullifier_deriving_key,
+            )?,
+        };
+
+        Ok(Self {
+            view_key,
+            incoming_view_key,
+            outgoing_view_key,
+        })
+    }
+}
+
+impl From<&SaplingKey> for ViewKeys {
+    fn from(key: &SaplingKey) -> Self {
+        Self {
+            view_key: key.view_key().clone(),
+            incoming_view_key: key.incoming_view_key().clone(),
+            outgoing_view_key: key.outgoing_view_key().clone(),
+        }
+    }
+}
+
 /// Derive a shared secret key from a secret key and the other person's public
 /// key.
 ///
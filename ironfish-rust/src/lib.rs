@@ -12,6 +12,7 @@
 
 #[cfg(feature = "transaction-proofs")]
 mod sapling;
+mod sapling_verifier;
 
 pub mod assets;
 pub mod errors;
@@ -19,9 +20,13 @@ pub mod frost_utils;
 pub mod keys;
 pub mod merkle_note;
 pub mod merkle_note_hash;
+pub mod merkle_tree;
 pub mod mining;
 pub mod nacl;
 pub mod note;
+pub mod note_scanner;
+pub mod nullifier_index;
+pub mod payment_request;
 pub mod rolling_filter;
 pub mod serializing;
 pub mod transaction;
@@ -32,7 +37,11 @@ pub mod xchacha20poly1305;
 #[cfg(any(test, feature = "benchmark"))]
 pub mod test_util;
 
-#[cfg(feature = "transaction-proofs")]
+// Only used by the tests of the `serde` feature
+#[cfg(all(test, not(feature = "serde")))]
+use serde_json as _;
+
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 pub mod sapling_bls12;
 
 pub use {
@@ -42,6 +51,9 @@ pub use {
     merkle_note::MerkleNote,
     merkle_note_hash::MerkleNoteHash,
     note::Note,
+    note_scanner::NoteScanner,
+    payment_request::PaymentRequest,
+    sapling_verifier::SaplingVerifier,
     transaction::{outputs::OutputDescription, spends::SpendDescription, Transaction},
 };
 
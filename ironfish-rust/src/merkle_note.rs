@@ -6,7 +6,7 @@
 //! A tree containing these values can serve as a snapshot of the entire chain.
 
 use crate::{
-    errors::IronfishError,
+    errors::{ErrorContext, IronfishError},
     keys::EphemeralKeyPair,
     keys::{shared_secret, IncomingViewKey, OutgoingViewKey, PublicAddress},
     note::{Note, ENCRYPTED_NOTE_SIZE},
@@ -41,6 +41,16 @@ pub const NOTE_ENCRYPTION_MINER_KEYS: &[u8; NOTE_ENCRYPTION_KEY_SIZE] =
     b"Iron Fish note encryption miner key000000000000000000000000000000000000000000000";
 const SHARED_KEY_PERSONALIZATION: &[u8; 16] = b"Iron Fish Keyenc";
 
+/// Size of a serialized [`MerkleNote`].
+//   32  value commitment
+// + 32  note commitment
+// + 32  ephemeral public key
+// + 152 encrypted note
+// + 80  note encryption keys
+// = 328
+pub const MERKLE_NOTE_SIZE: usize =
+    32 + 32 + 32 + ENCRYPTED_NOTE_SIZE + aead::MAC_SIZE + NOTE_ENCRYPTION_KEY_SIZE;
+
 #[derive(Clone, Debug)]
 pub struct MerkleNote {
     /// Randomized value commitment. Sometimes referred to as
@@ -150,14 +160,18 @@ impl MerkleNote {
 
     /// Load a MerkleNote from the given reader.
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
-        let value_commitment = read_point(&mut reader)?;
-        let note_commitment = read_scalar(&mut reader)?;
-        let ephemeral_public_key = read_point(&mut reader)?;
+        let value_commitment = read_point(&mut reader).context("value_commitment")?;
+        let note_commitment = read_scalar(&mut reader).context("note_commitment")?;
+        let ephemeral_public_key = read_point(&mut reader).context("ephemeral_public_key")?;
 
         let mut encrypted_note = [0; ENCRYPTED_NOTE_SIZE + aead::MAC_SIZE];
-        reader.read_exact(&mut encrypted_note[..])?;
+        reader
+            .read_exact(&mut encrypted_note[..])
+            .context("encrypted_note")?;
         let mut note_encryption_keys = [0; NOTE_ENCRYPTION_KEY_SIZE];
-        reader.read_exact(&mut note_encryption_keys[..])?;
+        reader
+            .read_exact(&mut note_encryption_keys[..])
+            .context("note_encryption_keys")?;
 
         Ok(MerkleNote {
             value_commitment,
@@ -0,0 +1,427 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An append-only, fixed-depth merkle tree of note commitments.
+//!
+//! The hashing scheme mirrors the one used by the TypeScript `MerkleTree`:
+//! a node whose right subtree has no leaves yet is hashed by combining its
+//! left child with itself, and the tree is always padded up to
+//! [`TREE_DEPTH`] levels. Root hashes produced here are checked against the
+//! note commitments of the genesis blocks in the test suite.
+
+use crate::{
+    errors::{IronfishError, IronfishErrorKind},
+    witness::{Witness, WitnessNode},
+    MerkleNoteHash,
+};
+use blstrs::Scalar;
+use ironfish_zkp::constants::TREE_DEPTH;
+
+/// Maximum number of leaves in the tree. A tree of depth [`TREE_DEPTH`] could
+/// hold one more leaf, but the tree size is serialized as a `u32` in spend
+/// descriptions, so the tree is capped at `u32::MAX` leaves.
+pub const MAX_TREE_SIZE: u64 = u32::MAX as u64;
+
+/// Incremental note commitment tree.
+///
+/// Only the hashes of complete subtrees are stored; hashes of the incomplete
+/// nodes on the right edge of the tree are recomputed on demand for the
+/// requested tree size, which allows past roots and witnesses to be calculated
+/// without keeping any history around.
+#[derive(Clone, Debug)]
+pub struct CommitmentTree {
+    /// `nodes[0]` holds the leaves, and `nodes[level]` holds the hashes of all
+    /// the complete subtrees of height `level`, from left to right.
+    nodes: Vec<Vec<Scalar>>,
+}
+
+impl CommitmentTree {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![vec![]; TREE_DEPTH + 1],
+        }
+    }
+
+    /// Number of leaves currently in the tree.
+    pub fn size(&self) -> u64 {
+        self.nodes[0].len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+
+    /// Get the leaf hash at the given position, if it exists.
+    pub fn get(&self, position: u64) -> Option<MerkleNoteHash> {
+        self.nodes[0]
+            .get(usize::try_from(position).ok()?)
+            .map(|hash| MerkleNoteHash::new(*hash))
+    }
+
+    /// Add a new leaf to the tree, returning its position.
+    pub fn append(&mut self, hash: MerkleNoteHash) -> Result<u64, IronfishError> {
+        let position = self.size();
+        if position >= MAX_TREE_SIZE {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidTreeSize));
+        }
+
+        self.nodes[0].push(hash.0);
+
+        // Hash together every subtree that was completed by this leaf
+        let mut level = 0;
+        while level < TREE_DEPTH && self.nodes[level].len() % 2 == 0 {
+            let len = self.nodes[level].len();
+            let parent = MerkleNoteHash::combine_hash(
+                level,
+                &self.nodes[level][len - 2],
+                &self.nodes[level][len - 1],
+            );
+            self.nodes[level + 1].push(parent);
+            level += 1;
+        }
+
+        Ok(position)
+    }
+
+    /// Root hash of the tree with all its current leaves.
+    pub fn root_hash(&self) -> Result<Scalar, IronfishError> {
+        self.past_root(self.size())
+    }
+
+    /// Calculate what the root hash was at the time the tree contained
+    /// `past_size` leaves.
+    pub fn past_root(&self, past_size: u64) -> Result<Scalar, IronfishError> {
+        let right_edge = self.right_edge(past_size)?;
+        Ok(right_edge[TREE_DEPTH])
+    }
+
+    /// Construct a [`Witness`] for the leaf at `position` in the current tree.
+    pub fn witness(&self, position: u64) -> Result<Witness, IronfishError> {
+        self.witness_at_size(position, self.size())
+    }
+
+    /// Construct a [`Witness`] for the leaf at `position`, as it would have
+    /// been when the tree contained `size` leaves.
+    pub fn witness_at_size(&self, position: u64, size: u64) -> Result<Witness, IronfishError> {
+        if position >= size {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidTreePosition));
+        }
+
+        let tree_size = u32::try_from(size)
+            .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidTreeSize, e))?;
+        let right_edge = self.right_edge(size)?;
+
+        let mut auth_path = Vec::with_capacity(TREE_DEPTH);
+        for level in 0..TREE_DEPTH {
+            let index = position >> level;
+
+            let node = if index % 2 == 1 {
+                WitnessNode::Right(self.node_hash(level, index - 1, size, &right_edge)?)
+            } else if (index + 1) << level >= size {
+                // No right sibling yet, so the node is combined with itself
+                WitnessNode::Left(self.node_hash(level, index, size, &right_edge)?)
+            } else {
+                WitnessNode::Left(self.node_hash(level, index + 1, size, &right_edge)?)
+            };
+
+            auth_path.push(node);
+        }
+
+        Ok(Witness {
+            tree_size: usize::try_from(tree_size)?,
+            root_hash: right_edge[TREE_DEPTH],
+            auth_path,
+        })
+    }
+
+    /// Hash of the node at `(level, index)` in a tree of `size` leaves. The
+    /// node must contain at least one leaf.
+    fn node_hash(
+        &self,
+        level: usize,
+        index: u64,
+        size: u64,
+        right_edge: &[Scalar],
+    ) -> Result<Scalar, IronfishError> {
+        if (index + 1) << level <= size {
+            Ok(self.nodes[level][usize::try_from(index)?])
+        } else {
+            debug_assert_eq!(index, (size - 1) >> level);
+            Ok(right_edge[level])
+        }
+    }
+
+    /// Calculate, for every level, the hash of the node containing the last
+    /// leaf of a tree of `size` leaves. The last element is the root hash.
+    fn right_edge(&self, size: u64) -> Result<Vec<Scalar>, IronfishError> {
+        if size == 0 || size > self.size() {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidTreeSize));
+        }
+
+        let last = size - 1;
+        let mut right_edge = Vec::with_capacity(TREE_DEPTH + 1);
+        right_edge.push(self.nodes[0][usize::try_from(last)?]);
+
+        for level in 0..TREE_DEPTH {
+            let index = last >> level;
+            let current = right_edge[level];
+
+            let parent = if index % 2 == 1 {
+                let left = self.nodes[level][usize::try_from(index - 1)?];
+                MerkleNoteHash::combine_hash(level, &left, &current)
+            } else {
+                MerkleNoteHash::combine_hash(level, &current, &current)
+            };
+
+            right_edge.push(parent);
+        }
+
+        Ok(right_edge)
+    }
+}
+
+impl Default for CommitmentTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CommitmentTree;
+    use crate::{
+        errors::IronfishErrorKind,
+        witness::{WitnessNode, WitnessTrait},
+        MerkleNoteHash,
+    };
+    use blstrs::Scalar;
+    use ironfish_zkp::{constants::TREE_DEPTH, hex::hex_to_bytes};
+
+    fn leaf(i: u64) -> MerkleNoteHash {
+        MerkleNoteHash::new(Scalar::from(i + 1))
+    }
+
+    fn read_hash(hex: &str) -> MerkleNoteHash {
+        let bytes: [u8; 32] = hex_to_bytes(hex).unwrap();
+        MerkleNoteHash::read(&bytes[..]).unwrap()
+    }
+
+    /// Build a tree from the given note commitments and check that its root,
+    /// and the root of every witness, matches the root calculated by the
+    /// TypeScript merkle tree.
+    fn assert_tree_matches(leaves: &[&str], expected_root: &str) {
+        let mut tree = CommitmentTree::new();
+        for leaf in leaves {
+            tree.append(read_hash(leaf)).unwrap();
+        }
+
+        let expected_root = read_hash(expected_root).0;
+        assert_eq!(tree.root_hash().unwrap(), expected_root);
+
+        for (position, leaf) in leaves.iter().enumerate() {
+            let witness = tree.witness(position as u64).unwrap();
+            assert_eq!(witness.root_hash(), expected_root);
+            assert_eq!(witness.tree_size(), leaves.len() as u32);
+            assert!(witness.verify(&read_hash(leaf)));
+        }
+    }
+
+    /// Naive root calculation following the TypeScript merkle tree rules:
+    /// missing right children are replaced by their left sibling.
+    fn naive_root(leaves: &[Scalar]) -> Scalar {
+        let mut level_hashes = leaves.to_vec();
+        for depth in 0..TREE_DEPTH {
+            level_hashes = level_hashes
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    MerkleNoteHash::combine_hash(depth, &pair[0], right)
+                })
+                .collect();
+        }
+        level_hashes[0]
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let tree = CommitmentTree::new();
+
+        assert!(tree.is_empty());
+        assert_eq!(
+            tree.root_hash().unwrap_err().kind,
+            IronfishErrorKind::InvalidTreeSize
+        );
+        assert_eq!(
+            tree.witness(0).unwrap_err().kind,
+            IronfishErrorKind::InvalidTreePosition
+        );
+    }
+
+    /// Note commitments and `noteCommitment` header field of the devnet
+    /// genesis block (`ironfish/src/networks/definitions/devnet.ts`)
+    #[test]
+    fn test_devnet_genesis_note_commitment() {
+        assert_tree_matches(
+            &[
+                "91e09723300e771e5dfd503ecdc169b64a8ba40e9070ddf345b101d12ed66371",
+                "5a2f1709904e1481f56da0ed939051a45943184bdfc25de94b5013f429e15442",
+                "693e7bd3c1fd156c7af275483470180c1cebcb92fc0bc73805815c8d714a7971",
+            ],
+            "e69e6ef74efc55a2bf4b06717f43cae462f4df210622e63dad8fb7ad30f93711",
+        );
+    }
+
+    /// Note commitments and `noteCommitment` header field of the mainnet
+    /// genesis block (`ironfish/src/networks/definitions/mainnet.ts`)
+    #[test]
+    fn test_mainnet_genesis_note_commitment() {
+        assert_tree_matches(
+            &[
+                "2cbcb742d7c02857fcc7983fa34d4e180328c261b62594324c180bf1b6488a72",
+                "af8e4c968ba09a94f13c3f5cd64e481853cb5239ed11677747607990bc00ce43",
+                "298cae67a78312e32d7a14a45eb1764e01efba460a58e4bb3577972b8081062c",
+                "bac2c7accf31a3f9d7e08afa0d9ec54c0a63af9ff0221c647db1b14da99a7e0c",
+                "674774274523c5399eb422de4e16273b704344634af10f4b1c3b0d7a2c00996b",
+                "23515944a532e99dea1be3212399772173a5a28958a72ee7f9dcab324e2c2202",
+                "a6c02c03eb28cd78a0f66954b94cfa80e0fcf3d71a6ba215daaf878e55197e4d",
+                "0b703e0207ea6d3de3f77fa026c31f7c7fa3ed281452b1d31515221f0994750d",
+                "163c0cda0c1fd504a299c1c181d726d660554e8bd3de9c866ee6c298db47f818",
+                "9b79310515addbf1be2cef24e15fb20180990483a5f299c4ff35e142116fe56e",
+                "6f16f5e6ff9cb0110c868268a23d8b127a2d2af0e5f216460558d1ca54ad8219",
+                "1e995c0f5c21f260e946a1d6fe498d99aac6dc71d63cba31d5587d5f4471f20c",
+                "47af557c72ea0178190d5244b5ec5aa7961665c01fd2c7b7de617cc13ebc5145",
+                "de8895863c06035cbf5f1d1c1fead5e6fe17af39a5cb0939962865edbc8fef3c",
+                "1cc40a89e97086bd404dbc2d1defa130f58ebf9df6ba5ac7c62c3000db782820",
+                "23479927eb0df24cd3e2765f372f3c2caacee688794ed7771b888cf0f57ee263",
+                "856cbb143cf76baac67203b1c857049ae4507b87615a0438e357830094cb1b31",
+                "fd2f1d45147bfaa56b31c21d205fda1cd56f602270381e11a5cc299848041817",
+                "992987df02c73002d1ea5fc47e3783ae6e1f749b13ef329a957adadf5262483b",
+                "7f4461bd9b5ec526fd5e7f696bb1f418da949e1fc2a5b0302e351dd5d079164e",
+                "e288f05b480147fa8e24b70fb26997569a36a58ab0b0060c5d50dddeb1821d02",
+                "f5515e569e439d9333f29903208638c1757ce246343832fe011c18a5da092a13",
+                "c372c6124c9c1e692395c1604d2ed659a275671811cccbf0913a708c96d77b14",
+                "d5935e2f8b2d2118aeeaf518d2bbb96b0a89b4e1263d7a264ecb24e025d8a33f",
+                "ca061073572c6704ae32c90ca2139755dc0b46e35fd24d0a9a7b024f5ff84f16",
+                "3cc99e0ce8980cdbb5978fef2f9eac032087f6c5c56d72d02bd92fa5ab2c3073",
+                "222facac9492f8875bd12ac442c6763bdd31911aae78dfa80e7b69dd8c93422d",
+                "16b19a841a0cb46e2b2440ba4e63bc35ad493652acafa2db841d54704ddc7416",
+                "6e6673cb8213345308e9b5a389239a7cf3c0de40b3e154dae83b67ace9458c23",
+                "71c8b5206e435b495bb235ee0362048e171ebcce508de038b51e8aa20f3b810d",
+                "33c1f7fa8ce922490b568c8f35fe97871668c78dbc9a5d95cd54690622958c16",
+                "82997852aeb84e9829c5f58cf00dfe16c9582fdbc01d0339c1ac188df6bc1546",
+                "b23b75486a05a46dbc7063a71a5de662b7da1c9c3327b695f9b4de0acf07390f",
+                "14ced36ee5485d48e8a50e9eb0e14381022b6b8db0910eb33b122f077f7d8337",
+                "f2ab91e21e282e3996489d8fe8fce95e0551bc9b23fe8becc0d801bc83ad5152",
+                "d9e9e96426e0ca600d76f75cabc65fa0d3f09811fe450f4fe8f264421478255b",
+                "7ad989f6e2a9c2f7db954d0693726e3fb397920f6fd4839ff1880259fc75b942",
+                "ed1dfa2adb93b44d0977283856663f87a72adc3efbe29f9f3ed4be4456576a15",
+                "2dd973a32c4d02efef7f5cea82b05aa4e7d1e276e30ee08961914fc739b9dc41",
+                "6a04f821fb7faf63b6855171e47177915fcc00425f7baba3f04c1650dcdd8b47",
+                "c04fcaf265c4236591df5239df84b498ce8a8365b1651633a908c9ca5e07b504",
+                "47a368761f1656c97d8c0f3cdef4cdcfdc7821856eb496873a473e477161dd2c",
+                "1ab491c8670344adbd76fce00c72749d81a9f5bdac048bbefb897526d1d3c86f",
+                "b07e0138fcbec30f4434d0d560fd216346251099be24a31ccd4995661d06672b",
+                "6691e8cc1f363bc55825bb0068504cb2124e38f4a82a261912443c54fd044656",
+                "02c2706e0d271cdcd12316bd2efd2de6e14767fe70e6ffa280e60df0c9488b68",
+                "efabe44ecf3ac97006be663fd45e5c7a1dbb4e9db7b3279fdccb10cfde49b173",
+                "4b6364c792630dc88a011db46916d5f46b6562a7b815c563da30425a35804018",
+                "baa18c807710f928f0a2f78ce9aa2fea1c21a3a01f8eab86ead9e8de1d921e6a",
+                "10041906d456b5ee4287dfab78ce4104f47cf96bdcd280f757e1579add61ee66",
+                "321913dd5cd544da1dca15bdd52b4e2991de92a3f245bcc90fdd6ba764c3ce68",
+                "ccab187f709578a31326ebfed88bbee458361b6dd43cda50d47162ae7a006922",
+                "6df89a3f872b3ba2bcf266718794a4efd794f72f623ed55604aa293c05bb1421",
+                "6b770a5b48ff295e283fe4e1ddde7cce522522127ac676678b2d2ad22061ac73",
+                "6f297c805b9dcee9465a440623535aa89a7a0784ffeb5ffd3a6de5b71a8db906",
+                "79a20f5da224fd6be33f947199c59ccee1174544e9da9878a8fbd73869091714",
+                "2ec30e8730eb9f0d9f88de35a6e6990cedb681810d63a858489dc1e8f493dd19",
+                "1095b490f7dec96f2b5ad1d226edfd54c16f533cd0f6dea563eb111ae69d2636",
+                "b04154b9941e2e6d38be8c78b9dbe8ae7fbe64a539b980b99212a917b5bdb917",
+                "31706cd058543df70c7d372bc72b6e480262ee686aea2351ccf6114461f37341",
+                "1b48b502e6d636d6e3b290973e02290bc799434484c8b102abbffadb296f1c1e",
+                "28680755acdadd764e15e7452dda2b426b89a9f1c7c60756369dafec36066a32",
+                "cea1d6184f173731b0a392dc639223a76ac0778f2e40480b621cd26177465a42",
+                "7b17b65a39f5c6955220b481390e247e34d1e907fa917aacc5c351ab0eca8c18",
+                "644d23715e11dda0831886264bf6e563c3c81c61341d4bbc1e60da7946671d49",
+                "03909e7ddc632ebc65538b88d2d7a29830a237e7356112b16da87c78dba32465",
+                "138807b77bf2ca045a8bb8344e144a7f3cbe265c7cd144e7c7ca3d32eb964c5d",
+                "8be516e6363ebbc6682ccc0e26eb4de23bb675706ea74eddeb9473dbef33360b",
+                "235998c2968a16c1290d60a1c10aaefa4605ba18ef9f5d88c9ad9ada0f2fc75a",
+                "e86f192c65067f954ddf274646a91754a93b6b0de3320b9276bd80f9b9b66c26",
+                "d1e615e140af6a99754d9b593ae8ec4b2212f08264672093d51d4db8c8b79566",
+                "158beaee41243a0b3a6fee11e2d00c7815d16f464567973188ad1f47806a2221",
+                "6117762e48e67b07db865b3fbb417cd19c6441dc85924f4d71a191364f63980d",
+                "a0565fcff81ea210d354dcc8e7b8125a4fa3202448c7b00922857f1e276d4d3c",
+                "1dce19bef3d58a67ff32da32af2109d76a1ef6badcec7efc9e7055f417c7b549",
+            ],
+            "e0a19257d3e3ed704fb7317270094062ab9642e83a7e75bf7a57415dbb9e4a2b",
+        );
+    }
+
+    #[test]
+    fn test_append_and_past_roots() {
+        let mut tree = CommitmentTree::new();
+        let mut leaves = vec![];
+        let mut roots = vec![];
+
+        for i in 0..9 {
+            assert_eq!(tree.append(leaf(i)).unwrap(), i);
+            leaves.push(leaf(i).0);
+
+            let root = tree.root_hash().unwrap();
+            assert_eq!(root, naive_root(&leaves));
+            roots.push(root);
+        }
+
+        assert_eq!(tree.size(), 9);
+        assert_eq!(tree.get(4), Some(leaf(4)));
+        assert_eq!(tree.get(9), None);
+
+        for (size, root) in roots.iter().enumerate() {
+            assert_eq!(tree.past_root(size as u64 + 1).unwrap(), *root);
+        }
+
+        assert_eq!(
+            tree.past_root(10).unwrap_err().kind,
+            IronfishErrorKind::InvalidTreeSize
+        );
+    }
+
+    #[test]
+    fn test_witness() {
+        let mut tree = CommitmentTree::new();
+        for i in 0..7 {
+            tree.append(leaf(i)).unwrap();
+        }
+
+        for size in 1..=7 {
+            for position in 0..size {
+                let witness = tree.witness_at_size(position, size).unwrap();
+
+                assert_eq!(witness.tree_size(), size as u32);
+                assert_eq!(witness.root_hash(), tree.past_root(size).unwrap());
+                assert_eq!(witness.auth_path.len(), TREE_DEPTH);
+                assert!(witness.verify(&leaf(position)));
+                assert!(!witness.verify(&leaf(position + 1)));
+
+                // The auth path sides must encode the leaf position
+                let mut witness_position = 0;
+                for (i, node) in witness.auth_path.iter().enumerate() {
+                    if let WitnessNode::Right(_) = node {
+                        witness_position |= 1 << i;
+                    }
+                }
+                assert_eq!(witness_position, position);
+            }
+        }
+
+        assert_eq!(
+            tree.witness_at_size(3, 3).unwrap_err().kind,
+            IronfishErrorKind::InvalidTreePosition
+        );
+        assert_eq!(
+            tree.witness_at_size(0, 8).unwrap_err().kind,
+            IronfishErrorKind::InvalidTreeSize
+        );
+    }
+}
@@ -1,6 +1,12 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+pub mod algorithm;
+pub mod benchmark;
+pub mod dataset;
 mod mine;
+pub mod stats;
+#[cfg(feature = "stratum")]
+pub mod stratum;
 mod thread;
 pub mod threadpool;
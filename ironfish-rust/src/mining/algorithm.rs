@@ -0,0 +1,293 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use fish_hash::Context;
+
+use super::{dataset::FishHashDataset, mine};
+
+/// Name of the [`Blake3`] algorithm in an [`AlgorithmRegistry`].
+pub const BLAKE3: &str = "blake3";
+/// Name of the [`FishHash`] algorithm in an [`AlgorithmRegistry`].
+pub const FISH_HASH: &str = "fishhash";
+
+/// A hash function used to mine blocks.
+///
+/// Each mining thread owns its own instance, so implementations can keep
+/// mutable state such as a dataset or scratch buffers between batches.
+pub trait MiningAlgorithm: Send {
+    /// Search the randomness values `start`, `start + step_size`, ... up to
+    /// `start + batch_size` for one whose header hash is lower than or equal
+    /// to `target`, as a 32 byte big endian number. The randomness is written
+    /// to `header_bytes`, after the first `xn_length` bytes which are the
+    /// extra nonce of the pool.
+    ///
+    /// Returns the randomness of the first match, with its extra nonce.
+    fn mine_batch(
+        &mut self,
+        header_bytes: &mut [u8],
+        xn_length: u8,
+        target: &[u8],
+        start: u64,
+        step_size: usize,
+        batch_size: u64,
+    ) -> Option<u64>;
+
+    /// The hash of a header, such as the header of a match found by
+    /// [`MiningAlgorithm::mine_batch`].
+    fn hash(&mut self, header_bytes: &[u8]) -> [u8; 32];
+}
+
+/// Mining with blake3, where the randomness is the first 8 bytes of the
+/// header.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake3;
+
+impl MiningAlgorithm for Blake3 {
+    fn mine_batch(
+        &mut self,
+        header_bytes: &mut [u8],
+        xn_length: u8,
+        target: &[u8],
+        start: u64,
+        step_size: usize,
+        batch_size: u64,
+    ) -> Option<u64> {
+        mine::mine_batch_blake3(
+            header_bytes,
+            xn_length,
+            target,
+            start,
+            step_size,
+            batch_size,
+        )
+    }
+
+    fn hash(&mut self, header_bytes: &[u8]) -> [u8; 32] {
+        blake3::hash(header_bytes).into()
+    }
+}
+
+/// Mining with FishHash, where the randomness is the last 8 bytes of the
+/// header.
+pub struct FishHash {
+    source: FishHashSource,
+}
+
+enum FishHashSource {
+    Context(Context),
+    Dataset(Arc<FishHashDataset>),
+}
+
+impl FishHash {
+    /// Create the FishHash context. The full context takes several minutes to
+    /// build and several gigabytes of memory, but hashes much faster than the
+    /// light context.
+    pub fn new(full_context: bool) -> Self {
+        Self::with_context(Context::new(full_context, None))
+    }
+
+    pub fn with_context(context: Context) -> Self {
+        FishHash {
+            source: FishHashSource::Context(context),
+        }
+    }
+
+    /// Hash with a prebuilt dataset, which can be shared with other
+    /// instances.
+    pub fn with_dataset(dataset: Arc<FishHashDataset>) -> Self {
+        FishHash {
+            source: FishHashSource::Dataset(dataset),
+        }
+    }
+}
+
+impl MiningAlgorithm for FishHash {
+    fn mine_batch(
+        &mut self,
+        header_bytes: &mut [u8],
+        xn_length: u8,
+        target: &[u8],
+        start: u64,
+        step_size: usize,
+        batch_size: u64,
+    ) -> Option<u64> {
+        mine::mine_batch_fish_hash(
+            |header| self.hash(header),
+            header_bytes,
+            xn_length,
+            target,
+            start,
+            step_size,
+            batch_size,
+        )
+    }
+
+    fn hash(&mut self, header_bytes: &[u8]) -> [u8; 32] {
+        match &mut self.source {
+            FishHashSource::Context(context) => {
+                let mut hash = [0u8; 32];
+                fish_hash::hash(&mut hash, context, header_bytes);
+                hash
+            }
+            FishHashSource::Dataset(dataset) => dataset.hash(header_bytes),
+        }
+    }
+}
+
+/// Creates an instance of an algorithm for a mining thread.
+pub type AlgorithmFactory = Arc<dyn Fn() -> Box<dyn MiningAlgorithm> + Send + Sync>;
+
+/// The mining algorithms available to a
+/// [`ThreadPool`](super::threadpool::ThreadPool), by name.
+#[derive(Clone, Default)]
+pub struct AlgorithmRegistry {
+    algorithms: Vec<(String, AlgorithmFactory)>,
+}
+
+impl AlgorithmRegistry {
+    /// Create a registry without any algorithm.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with [`BLAKE3`] and, if enabled, [`FISH_HASH`].
+    pub fn with_defaults(use_fish_hash: bool, fish_hash_full_context: bool) -> Self {
+        let mut registry = Self::new();
+        registry.register(BLAKE3, || Box::new(Blake3));
+        if use_fish_hash {
+            registry.register(FISH_HASH, move || {
+                Box::new(FishHash::new(fish_hash_full_context))
+            });
+        }
+        registry
+    }
+
+    /// Create a registry with [`BLAKE3`] and [`FISH_HASH`], where FishHash
+    /// uses the full dataset saved at `dataset_path`.
+    ///
+    /// The dataset is loaded by the first instance of FishHash to be created,
+    /// and shared with the other instances. If the file is missing or
+    /// corrupted, the dataset is built with `build_threads` threads and saved.
+    /// See [`FishHashDataset::load_or_build`].
+    pub fn with_fish_hash_dataset(dataset_path: impl Into<PathBuf>, build_threads: usize) -> Self {
+        let dataset_path = dataset_path.into();
+        let dataset = Arc::new(OnceLock::new());
+
+        let mut registry = Self::new();
+        registry.register(BLAKE3, || Box::new(Blake3));
+        registry.register(FISH_HASH, move || {
+            let dataset = dataset.get_or_init(|| {
+                Arc::new(FishHashDataset::load_or_build(&dataset_path, build_threads))
+            });
+            Box::new(FishHash::with_dataset(dataset.clone()))
+        });
+        registry
+    }
+
+    /// Register an algorithm, replacing any algorithm with the same name.
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F)
+    where
+        F: Fn() -> Box<dyn MiningAlgorithm> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        let factory: AlgorithmFactory = Arc::new(factory);
+
+        match self.index_of(&name) {
+            Some(index) => self.algorithms[index].1 = factory,
+            None => self.algorithms.push((name, factory)),
+        }
+    }
+
+    /// The names of the algorithms, in order of registration.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.algorithms.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.index_of(name).is_some()
+    }
+
+    /// Create an instance of the algorithm with the given name.
+    pub fn create(&self, name: &str) -> Option<Box<dyn MiningAlgorithm>> {
+        self.index_of(name)
+            .map(|index| (self.algorithms[index].1)())
+    }
+
+    pub fn len(&self) -> usize {
+        self.algorithms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.algorithms.is_empty()
+    }
+
+    pub(super) fn index_of(&self, name: &str) -> Option<usize> {
+        self.algorithms.iter().position(|(n, _)| n == name)
+    }
+
+    /// Create an instance of every algorithm, in order of registration.
+    pub(super) fn create_all(&self) -> Vec<Box<dyn MiningAlgorithm>> {
+        self.algorithms
+            .iter()
+            .map(|(_, factory)| factory())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AlgorithmRegistry, Blake3, MiningAlgorithm, BLAKE3, FISH_HASH};
+
+    /// Matches every randomness, to tell which algorithm was used.
+    struct AlwaysMatch;
+
+    impl MiningAlgorithm for AlwaysMatch {
+        fn mine_batch(
+            &mut self,
+            _header_bytes: &mut [u8],
+            _xn_length: u8,
+            _target: &[u8],
+            start: u64,
+            _step_size: usize,
+            _batch_size: u64,
+        ) -> Option<u64> {
+            Some(start)
+        }
+
+        fn hash(&mut self, _header_bytes: &[u8]) -> [u8; 32] {
+            [0; 32]
+        }
+    }
+
+    #[test]
+    fn test_algorithm_registry() {
+        let mut registry = AlgorithmRegistry::with_defaults(false, false);
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec![BLAKE3]);
+        assert!(!registry.contains(FISH_HASH));
+
+        registry.register("custom", || Box::new(Blake3));
+        assert_eq!(registry.len(), 2);
+
+        // Registering an algorithm again replaces it, keeping its position
+        registry.register(BLAKE3, || Box::new(AlwaysMatch));
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec![BLAKE3, "custom"]);
+
+        let header_bytes = &mut [0u8; 8];
+        let target = &[0u8; 32];
+        let mut replaced = registry.create(BLAKE3).unwrap();
+        assert_eq!(
+            replaced.mine_batch(header_bytes, 0, target, 7, 1, 10),
+            Some(7)
+        );
+        let mut custom = registry.create("custom").unwrap();
+        assert_eq!(custom.mine_batch(header_bytes, 0, target, 7, 1, 10), None);
+
+        assert!(registry.create("unknown").is_none());
+        assert_eq!(registry.create_all().len(), 2);
+    }
+}
@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Measurement of the hash rate of the mining algorithms, to choose the
+//! thread count and batch size of a [`ThreadPool`](super::threadpool::ThreadPool).
+
+use std::{
+    sync::Barrier,
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::algorithm::AlgorithmRegistry;
+
+/// Size of a serialized block header.
+const HEADER_SIZE: usize = 180;
+
+/// The hash rate of an algorithm with a given thread count and batch size.
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+    pub algorithm: String,
+    pub thread_count: usize,
+    pub batch_size: u32,
+    /// Hashes computed by all the threads
+    pub hashes: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    pub fn hashes_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        self.hashes as f64 / seconds
+    }
+}
+
+/// Mine with every algorithm of the registry, for each combination of
+/// thread count and batch size, for about `duration` each.
+///
+/// Threads search the nonces in the same way as the threads of a
+/// [`ThreadPool`](super::threadpool::ThreadPool), with a target that is
+/// never met. The algorithms are created before the measurement starts, so
+/// the time to build a FishHash context is not included.
+pub fn benchmark(
+    algorithms: &AlgorithmRegistry,
+    thread_counts: &[usize],
+    batch_sizes: &[u32],
+    duration: Duration,
+) -> Vec<BenchmarkResult> {
+    let mut results = Vec::new();
+
+    for name in algorithms.names() {
+        for &thread_count in thread_counts.iter().filter(|count| **count > 0) {
+            for &batch_size in batch_sizes.iter().filter(|size| **size > 0) {
+                let (hashes, elapsed) =
+                    run(algorithms, name, thread_count, batch_size as u64, duration);
+
+                results.push(BenchmarkResult {
+                    algorithm: name.to_string(),
+                    thread_count,
+                    batch_size,
+                    hashes,
+                    elapsed,
+                });
+            }
+        }
+    }
+
+    results
+}
+
+/// The result with the highest hash rate for the given algorithm.
+pub fn fastest<'a>(results: &'a [BenchmarkResult], algorithm: &str) -> Option<&'a BenchmarkResult> {
+    results
+        .iter()
+        .filter(|result| result.algorithm == algorithm)
+        .max_by(|a, b| a.hashes_per_second().total_cmp(&b.hashes_per_second()))
+}
+
+/// Mine with `thread_count` threads until `duration` elapses, returning the
+/// number of hashes and the time taken by the slowest thread.
+fn run(
+    algorithms: &AlgorithmRegistry,
+    name: &str,
+    thread_count: usize,
+    batch_size: u64,
+    duration: Duration,
+) -> (u64, Duration) {
+    let barrier = Barrier::new(thread_count);
+
+    thread::scope(|scope| {
+        let handles = (0..thread_count)
+            .map(|id| {
+                let barrier = &barrier;
+
+                scope.spawn(move || {
+                    let mut algorithm = algorithms
+                        .create(name)
+                        .expect("the algorithm should be registered");
+                    let mut header_bytes = [0u8; HEADER_SIZE];
+                    let target = [0u8; 32];
+                    let mut hashes = 0;
+                    let mut batch_start = id as u64;
+
+                    barrier.wait();
+                    let start = Instant::now();
+                    while start.elapsed() < duration {
+                        let _ = algorithm.mine_batch(
+                            &mut header_bytes,
+                            0,
+                            &target,
+                            batch_start,
+                            thread_count,
+                            batch_size,
+                        );
+                        hashes += batch_size / thread_count as u64;
+                        batch_start +=
+                            batch_size + thread_count as u64 - (batch_size % thread_count as u64);
+                    }
+
+                    (hashes, start.elapsed())
+                })
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .fold((0, Duration::ZERO), |(hashes, elapsed), result| {
+                (hashes + result.0, elapsed.max(result.1))
+            })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{benchmark, fastest};
+    use crate::mining::algorithm::{AlgorithmRegistry, BLAKE3};
+
+    #[test]
+    fn test_benchmark() {
+        let algorithms = AlgorithmRegistry::with_defaults(false, false);
+        let results = benchmark(
+            &algorithms,
+            &[1, 2, 0],
+            &[100, 1000],
+            Duration::from_millis(20),
+        );
+
+        // Thread counts of zero are skipped
+        assert_eq!(results.len(), 4);
+        for result in results.iter() {
+            assert_eq!(result.algorithm, BLAKE3);
+            assert!(result.hashes > 0);
+            assert!(result.elapsed >= Duration::from_millis(20));
+            assert!(result.hashes_per_second() > 0.0);
+        }
+
+        let best = fastest(&results, BLAKE3).unwrap();
+        assert!(results
+            .iter()
+            .all(|result| result.hashes_per_second() <= best.hashes_per_second()));
+        assert!(fastest(&results, "unknown").is_none());
+    }
+}
@@ -0,0 +1,400 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The full FishHash dataset, saved to a file once it is built and memory
+//! mapped on later runs, so that it can be shared by all the threads of a
+//! [`ThreadPool`](super::threadpool::ThreadPool) and across processes.
+
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use fish_hash::{Context, HashData};
+use memmap2::Mmap;
+
+use crate::errors::{IronfishError, IronfishErrorKind};
+
+// Parameters of FishHash, as defined in the fish_hash crate
+const FNV_PRIME: u32 = 0x01000193;
+const NUM_DATASET_ACCESSES: usize = 32;
+const FULL_DATASET_NUM_ITEMS: u32 = 37748717;
+
+/// Size of an item of the dataset.
+const ITEM_SIZE: usize = 128;
+/// Number of 32 bit words in an item of the dataset.
+const ITEM_WORDS: usize = ITEM_SIZE / 4;
+
+/// Identifies a dataset file, followed by [`FILE_VERSION`].
+const FILE_MAGIC: [u8; 8] = *b"FISHDSET";
+const FILE_VERSION: u32 = 1;
+/// Size of the header of a dataset file, which is made of the magic bytes,
+/// the version, the item size, the item count and the blake3 hash of the
+/// items, padded so that the items are aligned.
+const HEADER_SIZE: usize = 64;
+
+enum Items {
+    Mapped(Mmap),
+    Built(Context),
+}
+
+/// The prebuilt FishHash dataset, which only needs to be read to compute
+/// hashes. Unlike [`fish_hash::Context`], it can be shared between threads.
+pub struct FishHashDataset {
+    items: Items,
+    item_count: usize,
+}
+
+impl FishHashDataset {
+    /// Build the full dataset in memory with `threads` threads. This takes
+    /// several minutes and about 4.6 GB of memory.
+    pub fn build(threads: usize) -> Self {
+        let mut context = Context::new(true, None);
+        context.prebuild_dataset(threads.max(1));
+
+        FishHashDataset {
+            item_count: FULL_DATASET_NUM_ITEMS as usize,
+            items: Items::Built(context),
+        }
+    }
+
+    /// Memory map a dataset file written by [`FishHashDataset::save`].
+    ///
+    /// Fails with [`IronfishErrorKind::InvalidFishHashDataset`] if the file is
+    /// not a complete dataset or if its items do not match their checksum.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, IronfishError> {
+        Self::load_items(path.as_ref(), FULL_DATASET_NUM_ITEMS as usize)
+    }
+
+    /// Load the dataset from `path`. If the file is missing or is invalid, the
+    /// dataset is built with `threads` threads and saved to `path`.
+    ///
+    /// The dataset is still returned if it cannot be saved, in which case it
+    /// is built again on the next call.
+    pub fn load_or_build<P: AsRef<Path>>(path: P, threads: usize) -> Self {
+        let path = path.as_ref();
+        if let Ok(dataset) = Self::load(path) {
+            return dataset;
+        }
+
+        let dataset = Self::build(threads);
+        let _ = dataset.save(path);
+        dataset
+    }
+
+    /// Write the dataset to `path`, through a temporary file so that the file
+    /// at `path` is either the previous file or the complete dataset.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), IronfishError> {
+        let path = path.as_ref();
+        let temp_path = path.with_extension("tmp");
+
+        let result = self.write_file(&temp_path);
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+            return result;
+        }
+
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// The FishHash hash of a header, which is the same as [`fish_hash::hash`]
+    /// with a full context.
+    pub fn hash(&self, header: &[u8]) -> [u8; 32] {
+        hash_with(header, |index| self.item_words(index))
+    }
+
+    fn load_items(path: &Path, item_count: usize) -> Result<Self, IronfishError> {
+        let file = File::open(path)?;
+        // SAFETY: the file must not be modified while it is mapped. Dataset
+        // files are only written through a temporary file that is renamed,
+        // which does not change the mapped file.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let invalid = |context| {
+            IronfishError::new(IronfishErrorKind::InvalidFishHashDataset).with_context(context)
+        };
+
+        if mmap.len() < HEADER_SIZE || mmap[..8] != FILE_MAGIC {
+            return Err(invalid("magic"));
+        }
+        let (version, item_size, count, checksum) = read_header(&mmap[..HEADER_SIZE]);
+        if version != FILE_VERSION {
+            return Err(invalid("version"));
+        }
+        if item_size as usize != ITEM_SIZE || count != item_count as u64 {
+            return Err(invalid("item_count"));
+        }
+        if mmap.len() != HEADER_SIZE + item_count * ITEM_SIZE {
+            return Err(invalid("items"));
+        }
+        if *blake3::hash(&mmap[HEADER_SIZE..]).as_bytes() != checksum {
+            return Err(invalid("checksum"));
+        }
+
+        Ok(FishHashDataset {
+            items: Items::Mapped(mmap),
+            item_count,
+        })
+    }
+
+    fn write_file(&self, path: &Path) -> Result<(), IronfishError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        // The header is written once the checksum of the items is known
+        writer.write_all(&[0; HEADER_SIZE])?;
+
+        let mut hasher = blake3::Hasher::new();
+        for index in 0..self.item_count {
+            let item = self.item(index);
+            hasher.update(item);
+            writer.write_all(item)?;
+        }
+
+        let mut file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&write_header(
+            self.item_count as u64,
+            hasher.finalize().as_bytes(),
+        ))?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    fn item(&self, index: usize) -> &[u8] {
+        match &self.items {
+            Items::Mapped(mmap) => {
+                let start = HEADER_SIZE + index * ITEM_SIZE;
+                &mmap[start..start + ITEM_SIZE]
+            }
+            Items::Built(context) => {
+                let items = context
+                    .full_dataset
+                    .as_ref()
+                    .expect("the full dataset should be allocated");
+                items[index].as_bytes()
+            }
+        }
+    }
+
+    fn item_words(&self, index: usize) -> [u32; ITEM_WORDS] {
+        let item = self.item(index);
+        let mut words = [0u32; ITEM_WORDS];
+        for (word, bytes) in words.iter_mut().zip(item.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        words
+    }
+}
+
+fn read_header(header: &[u8]) -> (u32, u32, u64, [u8; 32]) {
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    let item_size = u32::from_le_bytes(header[12..16].try_into().unwrap());
+    let item_count = u64::from_le_bytes(header[16..24].try_into().unwrap());
+    let checksum = header[24..56].try_into().unwrap();
+    (version, item_size, item_count, checksum)
+}
+
+fn write_header(item_count: u64, checksum: &[u8; 32]) -> [u8; HEADER_SIZE] {
+    let mut header = [0u8; HEADER_SIZE];
+    header[..8].copy_from_slice(&FILE_MAGIC);
+    header[8..12].copy_from_slice(&FILE_VERSION.to_le_bytes());
+    header[12..16].copy_from_slice(&(ITEM_SIZE as u32).to_le_bytes());
+    header[16..24].copy_from_slice(&item_count.to_le_bytes());
+    header[24..56].copy_from_slice(checksum);
+    header
+}
+
+fn fnv1(u: u32, v: u32) -> u32 {
+    u.wrapping_mul(FNV_PRIME) ^ v
+}
+
+/// The FishHash hash of a header, reading the items of the dataset with
+/// `lookup`.
+fn hash_with<F>(header: &[u8], mut lookup: F) -> [u8; 32]
+where
+    F: FnMut(usize) -> [u32; ITEM_WORDS],
+{
+    let mut seed = [0u8; 64];
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(header);
+    hasher.finalize_xof().fill(&mut seed);
+
+    let mut mix = [0u32; ITEM_WORDS];
+    for (word, bytes) in mix.iter_mut().zip(seed.chunks_exact(4).cycle()) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    for _ in 0..NUM_DATASET_ACCESSES {
+        let fetch0 = lookup((mix[0] % FULL_DATASET_NUM_ITEMS) as usize);
+        let mut fetch1 = lookup((mix[4] % FULL_DATASET_NUM_ITEMS) as usize);
+        let mut fetch2 = lookup((mix[8] % FULL_DATASET_NUM_ITEMS) as usize);
+
+        for j in 0..ITEM_WORDS {
+            fetch1[j] = fnv1(mix[j], fetch1[j]);
+            fetch2[j] ^= mix[j];
+        }
+
+        // Combine the items as 64 bit little endian words
+        let word = |words: &[u32; ITEM_WORDS], j: usize| {
+            words[2 * j] as u64 | (words[2 * j + 1] as u64) << 32
+        };
+        for j in 0..ITEM_WORDS / 2 {
+            let value = word(&fetch0, j)
+                .wrapping_mul(word(&fetch1, j))
+                .wrapping_add(word(&fetch2, j));
+            mix[2 * j] = value as u32;
+            mix[2 * j + 1] = (value >> 32) as u32;
+        }
+    }
+
+    let mut final_data = [0u8; 96];
+    final_data[..64].copy_from_slice(&seed);
+    for (i, words) in mix.chunks_exact(4).enumerate() {
+        let mix_hash = fnv1(fnv1(fnv1(words[0], words[1]), words[2]), words[3]);
+        final_data[64 + i * 4..68 + i * 4].copy_from_slice(&mix_hash.to_le_bytes());
+    }
+
+    blake3::hash(&final_data).into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, path::PathBuf};
+
+    use fish_hash::{Context, Hash1024, Hash512, HashData};
+
+    use super::{fnv1, hash_with, FishHashDataset, Items, ITEM_WORDS};
+    use crate::errors::IronfishErrorKind;
+
+    const LIGHT_CACHE_NUM_ITEMS: u32 = 1179641;
+    const FULL_DATASET_ITEM_PARENTS: u32 = 512;
+
+    /// Compute an item of the dataset from the light cache, as the fish_hash
+    /// crate does when the dataset is not built.
+    fn light_item(light_cache: &[Hash512], index: usize) -> [u32; ITEM_WORDS] {
+        let words = |hash: &Hash512| {
+            let mut words = [0u32; 16];
+            for (i, word) in words.iter_mut().enumerate() {
+                *word = hash.get_as_u32(i);
+            }
+            words
+        };
+        let keccak = |words: &mut [u32; 16]| {
+            let mut bytes = [0u8; 64];
+            for (i, word) in words.iter().enumerate() {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+            }
+            fish_hash::keccak_in_place(&mut bytes);
+            for (i, word) in words.iter_mut().enumerate() {
+                *word = u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap());
+            }
+        };
+        let parent = |index: u32| words(&light_cache[(index % LIGHT_CACHE_NUM_ITEMS) as usize]);
+
+        let mut item = [0u32; ITEM_WORDS];
+        for half in 0..2 {
+            let seed = (index * 2) as u32 + half as u32;
+            let mut mix = parent(seed);
+            mix[0] ^= seed;
+            keccak(&mut mix);
+
+            for j in 0..FULL_DATASET_ITEM_PARENTS {
+                let parent = parent(fnv1(seed ^ j, mix[(j % 16) as usize]));
+                for (word, parent_word) in mix.iter_mut().zip(parent) {
+                    *word = fnv1(*word, parent_word);
+                }
+            }
+            keccak(&mut mix);
+
+            item[half * 16..half * 16 + 16].copy_from_slice(&mix);
+        }
+        item
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ironfish-{}-{}", name, std::process::id()))
+    }
+
+    /// A dataset with only `item_count` items, which is too small to hash
+    /// with.
+    fn small_dataset(item_count: usize) -> FishHashDataset {
+        let mut items = vec![Hash1024::new(); item_count];
+        for (index, item) in items.iter_mut().enumerate() {
+            item.as_bytes_mut().fill(index as u8 + 1);
+        }
+
+        FishHashDataset {
+            items: Items::Built(Context {
+                light_cache: Box::new([]),
+                full_dataset: Some(items.into_boxed_slice()),
+            }),
+            item_count,
+        }
+    }
+
+    #[test]
+    fn test_hash_matches_fish_hash() {
+        let mut context = Context::new(false, None);
+
+        for header in [
+            &b"the quick brown fox jumps over the lazy dog"[..],
+            &[7; 180],
+        ] {
+            let mut expected = [0u8; 32];
+            fish_hash::hash(&mut expected, &mut context, header);
+
+            let hash = hash_with(header, |index| light_item(&context.light_cache, index));
+            assert_eq!(hash, expected);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = test_path("dataset-save");
+        let dataset = small_dataset(16);
+        dataset.save(&path).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        let loaded = FishHashDataset::load_items(&path, 16).unwrap();
+        assert!(matches!(loaded.items, Items::Mapped(_)));
+        for index in 0..16 {
+            assert_eq!(loaded.item(index), dataset.item(index));
+            assert_eq!(loaded.item_words(index), dataset.item_words(index));
+        }
+
+        // A dataset of a different size, such as the full dataset, is invalid
+        let error = FishHashDataset::load(&path).err().unwrap();
+        assert_eq!(error.kind, IronfishErrorKind::InvalidFishHashDataset);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_corrupted() {
+        let path = test_path("dataset-corrupted");
+        small_dataset(16).save(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        fs::write(&path, &corrupted).unwrap();
+        let error = FishHashDataset::load_items(&path, 16).err().unwrap();
+        assert_eq!(error.kind, IronfishErrorKind::InvalidFishHashDataset);
+        assert_eq!(error.context_path().as_deref(), Some("checksum"));
+
+        fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+        let error = FishHashDataset::load_items(&path, 16).err().unwrap();
+        assert_eq!(error.context_path().as_deref(), Some("items"));
+
+        fs::write(&path, &bytes[1..]).unwrap();
+        let error = FishHashDataset::load_items(&path, 16).err().unwrap();
+        assert_eq!(error.context_path().as_deref(), Some("magic"));
+
+        fs::remove_file(&path).unwrap();
+        let error = FishHashDataset::load_items(&path, 16).err().unwrap();
+        assert_eq!(error.kind, IronfishErrorKind::Io);
+    }
+}
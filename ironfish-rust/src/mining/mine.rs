@@ -1,8 +1,6 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
-use fish_hash::Context;
-
 /// returns true if a <= b when treating both as 32 byte big endian numbers.
 pub(crate) fn bytes_lte(a: &[u8], b: &[u8]) -> bool {
     for i in 0..32 {
@@ -40,24 +38,24 @@ pub(crate) fn mine_batch_blake3(
     None
 }
 
-pub(crate) fn mine_batch_fish_hash(
-    context: &mut Context,
+pub(crate) fn mine_batch_fish_hash<F>(
+    mut hash: F,
     header_bytes: &mut [u8],
     xn_length: u8,
     target: &[u8],
     start: u64,
     step_size: usize,
     batch_size: u64,
-) -> Option<u64> {
+) -> Option<u64>
+where
+    F: FnMut(&[u8]) -> [u8; 32],
+{
     let end = start + batch_size;
     for i in (start..=end).step_by(step_size) {
         header_bytes[172 + xn_length as usize..]
             .copy_from_slice(&i.to_be_bytes()[xn_length as usize..]);
 
-        let mut hash = [0u8; 32];
-        {
-            fish_hash::hash(&mut hash, context, header_bytes);
-        }
+        let hash = hash(header_bytes);
 
         if bytes_lte(&hash, target) {
             let mut bytes = [0u8; 8];
@@ -129,7 +127,11 @@ mod test {
         ];
 
         let result = mine_batch_fish_hash(
-            context,
+            |header| {
+                let mut hash = [0u8; 32];
+                fish_hash::hash(&mut hash, context, header);
+                hash
+            },
             header_bytes,
             0,
             target,
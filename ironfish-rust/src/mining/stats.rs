@@ -0,0 +1,151 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Duration over which [`MiningStats::hash_rate`] is averaged.
+pub const HASH_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// A snapshot of the work of a [`ThreadPool`](super::threadpool::ThreadPool),
+/// returned by [`ThreadPool::stats`](super::threadpool::ThreadPool::stats).
+#[derive(Clone, Debug, PartialEq)]
+pub struct MiningStats {
+    /// Time since the thread pool was created
+    pub elapsed: Duration,
+    /// Hashes computed by each thread since the thread pool was created
+    pub thread_hashes: Vec<u64>,
+    /// Hashes per second, averaged over the last [`HASH_RATE_WINDOW`]
+    pub hash_rate: f64,
+    /// Randomness values found that meet the block target
+    pub blocks_found: u64,
+    /// Randomness values found that meet the share target
+    pub shares_found: u64,
+    /// Blocks and shares discarded because they were found for previous work
+    pub stale_discarded: u64,
+    /// Threads that have created their mining algorithms, which includes
+    /// building the FishHash context, and are able to mine
+    pub threads_ready: usize,
+}
+
+impl MiningStats {
+    pub fn total_hashes(&self) -> u64 {
+        self.thread_hashes
+            .iter()
+            .fold(0, |total, hashes| total.saturating_add(*hashes))
+    }
+
+    /// The fraction of threads that are ready to mine, between 0 and 1.
+    pub fn ready_progress(&self) -> f64 {
+        if self.thread_hashes.is_empty() {
+            return 1.0;
+        }
+
+        self.threads_ready as f64 / self.thread_hashes.len() as f64
+    }
+}
+
+/// Counters shared between a thread pool and its threads.
+pub(super) struct MiningCounters {
+    pub(super) thread_hashes: Vec<AtomicU64>,
+    pub(super) threads_ready: AtomicUsize,
+    pub(super) blocks_found: AtomicU64,
+    pub(super) shares_found: AtomicU64,
+}
+
+impl MiningCounters {
+    pub(super) fn new(thread_count: usize) -> Self {
+        MiningCounters {
+            thread_hashes: (0..thread_count).map(|_| AtomicU64::new(0)).collect(),
+            threads_ready: AtomicUsize::new(0),
+            blocks_found: AtomicU64::new(0),
+            shares_found: AtomicU64::new(0),
+        }
+    }
+
+    pub(super) fn add_hashes(&self, thread: usize, hashes: u64) {
+        self.thread_hashes[thread].fetch_add(hashes, Ordering::Relaxed);
+    }
+
+    pub(super) fn thread_hashes(&self) -> Vec<u64> {
+        self.thread_hashes
+            .iter()
+            .map(|hashes| hashes.load(Ordering::Relaxed))
+            .collect()
+    }
+}
+
+/// Samples of the total hash count, to compute the hash rate over a window.
+pub(super) struct HashRateWindow {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl HashRateWindow {
+    pub(super) fn new(start: Instant) -> Self {
+        HashRateWindow {
+            samples: VecDeque::from([(start, 0)]),
+        }
+    }
+
+    /// Record the total hash count at `now`, and return the hash rate since
+    /// the oldest sample in the window.
+    pub(super) fn sample(&mut self, now: Instant, total_hashes: u64) -> f64 {
+        // Keep the most recent sample older than the window, so that the rate
+        // covers the whole window
+        while self.samples.len() > 1
+            && now.saturating_duration_since(self.samples[1].0) >= HASH_RATE_WINDOW
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, total_hashes));
+
+        let (oldest_time, oldest_hashes) = self.samples[0];
+        let seconds = now.saturating_duration_since(oldest_time).as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+
+        total_hashes.saturating_sub(oldest_hashes) as f64 / seconds
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{HashRateWindow, MiningStats};
+
+    #[test]
+    fn test_hash_rate_window() {
+        let start = Instant::now();
+        let mut window = HashRateWindow::new(start);
+
+        assert_eq!(window.sample(start, 0), 0.0);
+        assert_eq!(window.sample(start + Duration::from_secs(2), 200), 100.0);
+        assert_eq!(window.sample(start + Duration::from_secs(4), 400), 100.0);
+
+        // Older samples leave the window: the rate is over the last 10 to
+        // 12 seconds
+        assert_eq!(window.sample(start + Duration::from_secs(12), 2400), 220.0);
+        assert_eq!(window.sample(start + Duration::from_secs(14), 2400), 200.0);
+    }
+
+    #[test]
+    fn test_mining_stats() {
+        let stats = MiningStats {
+            elapsed: Duration::from_secs(1),
+            thread_hashes: vec![u64::MAX, 5, 10],
+            hash_rate: 0.0,
+            blocks_found: 0,
+            shares_found: 0,
+            stale_discarded: 0,
+            threads_ready: 2,
+        };
+
+        assert_eq!(stats.total_hashes(), u64::MAX);
+        assert_eq!(stats.ready_progress(), 2.0 / 3.0);
+    }
+}
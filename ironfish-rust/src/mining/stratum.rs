@@ -0,0 +1,818 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The stratum protocol spoken between mining pools and their miners, and a
+//! [`StratumMiner`] that mines the work of a pool with a [`ThreadPool`].
+//!
+//! Messages are JSON objects, one per line, of the form
+//! `{"id": 1, "method": "mining.notify", "body": {...}}`. The id is a counter
+//! of the messages sent by each side. Binary fields are hex strings. Errors
+//! sent by the pool have the form `{"id": 1, "error": {"id": 1, "message": "..."}}`.
+//!
+//! Only version 3 of the protocol is supported: the pool assigns an extra
+//! nonce (`xn`) to each miner when it subscribes, which the miner writes at
+//! the start of the randomness of every header it mines.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::threadpool::ThreadPool;
+use crate::{
+    errors::{IronfishError, IronfishErrorKind},
+    serializing::{bytes_to_hex, hex_to_bytes, hex_to_vec_bytes},
+};
+
+/// Version of the protocol sent when subscribing.
+pub const STRATUM_VERSION: u32 = 3;
+
+/// Offset of the randomness in a header mined with FishHash. Headers mined
+/// with blake3 start with the randomness.
+const FISH_HASH_RANDOMNESS_OFFSET: usize = 172;
+
+/// How long [`StratumMiner::run`] waits for a message from the pool before
+/// checking for found blocks.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A message sent by a miner to a pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClientMessage {
+    /// `mining.subscribe`: request work for the given address
+    Subscribe {
+        version: u32,
+        name: Option<String>,
+        public_address: String,
+        agent: Option<String>,
+    },
+    /// `mining.submit`: a randomness, including the extra nonce, whose hash
+    /// meets the target of the pool
+    Submit {
+        mining_request_id: u32,
+        randomness: u64,
+    },
+}
+
+/// A message sent by a pool to a miner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerMessage {
+    /// `mining.subscribed`: the extra nonce assigned to the miner
+    Subscribed { client_id: u64, xn: Vec<u8> },
+    /// `mining.set_target`: the target that submitted hashes must meet
+    SetTarget { target: [u8; 32] },
+    /// `mining.notify`: a new header to mine, replacing the previous one
+    Notify {
+        mining_request_id: u32,
+        header: Vec<u8>,
+    },
+    /// `mining.wait_for_work`: stop mining until the next notify
+    WaitForWork,
+    /// `mining.submitted`: whether the submit with the given id was accepted
+    Submitted {
+        id: u64,
+        result: bool,
+        message: Option<String>,
+    },
+    /// `mining.disconnect`: the pool is closing the connection
+    Disconnect {
+        reason: Option<String>,
+        version_expected: Option<u32>,
+        banned_until: Option<u64>,
+        message: Option<String>,
+    },
+    /// An error about the message with the given id
+    Error { id: u64, message: String },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    id: u64,
+    method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ErrorEnvelope {
+    id: u64,
+    error: ErrorBody,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ErrorBody {
+    id: u64,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribeBody {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    public_address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmitBody {
+    mining_request_id: u32,
+    randomness: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscribedBody {
+    client_id: u64,
+    xn: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SetTargetBody {
+    target: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NotifyBody {
+    mining_request_id: u32,
+    header: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SubmittedBody {
+    id: u64,
+    result: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DisconnectBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version_expected: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    banned_until: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> IronfishError {
+    IronfishError::new_with_source(IronfishErrorKind::InvalidData, e)
+}
+
+fn to_line<T: Serialize>(value: &T) -> String {
+    // Serializing structs of strings and numbers cannot fail
+    let mut line = serde_json::to_string(value).expect("message should serialize");
+    line.push('\n');
+    line
+}
+
+fn encode<T: Serialize>(id: u64, method: &str, body: Option<T>) -> String {
+    to_line(&Envelope {
+        id,
+        method: method.to_string(),
+        body: body.map(|body| serde_json::to_value(body).expect("body should serialize")),
+    })
+}
+
+fn decode_body<T: DeserializeOwned>(envelope: Envelope) -> Result<T, IronfishError> {
+    serde_json::from_value(envelope.body.unwrap_or(Value::Null))
+        .map_err(|e| invalid_data(e).with_context(envelope.method))
+}
+
+fn randomness_to_hex(randomness: u64) -> String {
+    bytes_to_hex(&randomness.to_be_bytes())
+}
+
+fn randomness_from_hex(hex: &str) -> Result<u64, IronfishError> {
+    Ok(u64::from_be_bytes(hex_to_bytes(hex)?))
+}
+
+impl ClientMessage {
+    /// Encode the message as a line of JSON, including the trailing newline.
+    pub fn encode(&self, id: u64) -> String {
+        match self {
+            ClientMessage::Subscribe {
+                version,
+                name,
+                public_address,
+                agent,
+            } => encode(
+                id,
+                "mining.subscribe",
+                Some(SubscribeBody {
+                    version: *version,
+                    name: name.clone(),
+                    public_address: public_address.clone(),
+                    agent: agent.clone(),
+                }),
+            ),
+            ClientMessage::Submit {
+                mining_request_id,
+                randomness,
+            } => encode(
+                id,
+                "mining.submit",
+                Some(SubmitBody {
+                    mining_request_id: *mining_request_id,
+                    randomness: randomness_to_hex(*randomness),
+                }),
+            ),
+        }
+    }
+
+    /// Decode a line of JSON into the id and the message.
+    pub fn decode(line: &str) -> Result<(u64, Self), IronfishError> {
+        let envelope: Envelope = serde_json::from_str(line).map_err(invalid_data)?;
+        let id = envelope.id;
+
+        let message = match envelope.method.as_str() {
+            "mining.subscribe" => {
+                let body: SubscribeBody = decode_body(envelope)?;
+                ClientMessage::Subscribe {
+                    version: body.version,
+                    name: body.name,
+                    public_address: body.public_address,
+                    agent: body.agent,
+                }
+            }
+            "mining.submit" => {
+                let body: SubmitBody = decode_body(envelope)?;
+                ClientMessage::Submit {
+                    mining_request_id: body.mining_request_id,
+                    randomness: randomness_from_hex(&body.randomness)
+                        .map_err(|e| e.with_context("mining.submit.randomness"))?,
+                }
+            }
+            method => {
+                return Err(IronfishError::new(IronfishErrorKind::InvalidData)
+                    .with_context(method.to_string()))
+            }
+        };
+
+        Ok((id, message))
+    }
+}
+
+impl ServerMessage {
+    /// Encode the message as a line of JSON, including the trailing newline.
+    pub fn encode(&self, id: u64) -> String {
+        match self {
+            ServerMessage::Subscribed { client_id, xn } => encode(
+                id,
+                "mining.subscribed",
+                Some(SubscribedBody {
+                    client_id: *client_id,
+                    xn: bytes_to_hex(xn),
+                }),
+            ),
+            ServerMessage::SetTarget { target } => encode(
+                id,
+                "mining.set_target",
+                Some(SetTargetBody {
+                    target: bytes_to_hex(target),
+                }),
+            ),
+            ServerMessage::Notify {
+                mining_request_id,
+                header,
+            } => encode(
+                id,
+                "mining.notify",
+                Some(NotifyBody {
+                    mining_request_id: *mining_request_id,
+                    header: bytes_to_hex(header),
+                }),
+            ),
+            ServerMessage::WaitForWork => encode::<()>(id, "mining.wait_for_work", None),
+            ServerMessage::Submitted {
+                id: submit_id,
+                result,
+                message,
+            } => encode(
+                id,
+                "mining.submitted",
+                Some(SubmittedBody {
+                    id: *submit_id,
+                    result: *result,
+                    message: message.clone(),
+                }),
+            ),
+            ServerMessage::Disconnect {
+                reason,
+                version_expected,
+                banned_until,
+                message,
+            } => encode(
+                id,
+                "mining.disconnect",
+                Some(DisconnectBody {
+                    reason: reason.clone(),
+                    version_expected: *version_expected,
+                    banned_until: *banned_until,
+                    message: message.clone(),
+                }),
+            ),
+            ServerMessage::Error {
+                id: message_id,
+                message,
+            } => to_line(&ErrorEnvelope {
+                id,
+                error: ErrorBody {
+                    id: *message_id,
+                    message: message.clone(),
+                },
+            }),
+        }
+    }
+
+    /// Decode a line of JSON into the id and the message.
+    pub fn decode(line: &str) -> Result<(u64, Self), IronfishError> {
+        let value: Value = serde_json::from_str(line).map_err(invalid_data)?;
+
+        if value.get("error").is_some() {
+            let envelope: ErrorEnvelope =
+                serde_json::from_value(value).map_err(|e| invalid_data(e).with_context("error"))?;
+            return Ok((
+                envelope.id,
+                ServerMessage::Error {
+                    id: envelope.error.id,
+                    message: envelope.error.message,
+                },
+            ));
+        }
+
+        let envelope: Envelope = serde_json::from_value(value).map_err(invalid_data)?;
+        let id = envelope.id;
+
+        let message = match envelope.method.as_str() {
+            "mining.subscribed" => {
+                let body: SubscribedBody = decode_body(envelope)?;
+                ServerMessage::Subscribed {
+                    client_id: body.client_id,
+                    xn: hex_to_vec_bytes(&body.xn)
+                        .map_err(|e| IronfishError::from(e).with_context("mining.subscribed.xn"))?,
+                }
+            }
+            "mining.set_target" => {
+                let body: SetTargetBody = decode_body(envelope)?;
+                ServerMessage::SetTarget {
+                    target: hex_to_bytes(&body.target).map_err(|e| {
+                        IronfishError::from(e).with_context("mining.set_target.target")
+                    })?,
+                }
+            }
+            "mining.notify" => {
+                let body: NotifyBody = decode_body(envelope)?;
+                ServerMessage::Notify {
+                    mining_request_id: body.mining_request_id,
+                    header: hex_to_vec_bytes(&body.header)
+                        .map_err(|e| IronfishError::from(e).with_context("mining.notify.header"))?,
+                }
+            }
+            "mining.wait_for_work" => ServerMessage::WaitForWork,
+            "mining.submitted" => {
+                let body: SubmittedBody = decode_body(envelope)?;
+                ServerMessage::Submitted {
+                    id: body.id,
+                    result: body.result,
+                    message: body.message,
+                }
+            }
+            "mining.disconnect" => {
+                // The body of a disconnect is optional
+                let body = match envelope.body {
+                    Some(_) => decode_body(envelope)?,
+                    None => DisconnectBody {
+                        reason: None,
+                        version_expected: None,
+                        banned_until: None,
+                        message: None,
+                    },
+                };
+                ServerMessage::Disconnect {
+                    reason: body.reason,
+                    version_expected: body.version_expected,
+                    banned_until: body.banned_until,
+                    message: body.message,
+                }
+            }
+            method => {
+                return Err(IronfishError::new(IronfishErrorKind::InvalidData)
+                    .with_context(method.to_string()))
+            }
+        };
+
+        Ok((id, message))
+    }
+}
+
+/// Mines the work sent by a pool.
+///
+/// The miner only keeps the state of the protocol: messages from the pool
+/// are passed to [`StratumMiner::handle_message`], and the messages to send
+/// back are returned by [`StratumMiner::subscribe`] and
+/// [`StratumMiner::poll`]. [`StratumMiner::run`] does both over a TCP
+/// connection.
+pub struct StratumMiner {
+    thread_pool: ThreadPool,
+    fish_hash: bool,
+    public_address: String,
+    name: Option<String>,
+    next_message_id: u64,
+    client_id: Option<u64>,
+    xn: Option<Vec<u8>>,
+    target: [u8; 32],
+    accepted_shares: u64,
+    rejected_shares: u64,
+}
+
+impl StratumMiner {
+    /// Create a miner that mines with the threads of `thread_pool`, for the
+    /// account with the given public address. The thread pool must have
+    /// FishHash enabled if `fish_hash` is set.
+    pub fn new(
+        thread_pool: ThreadPool,
+        fish_hash: bool,
+        public_address: String,
+        name: Option<String>,
+    ) -> Self {
+        StratumMiner {
+            thread_pool,
+            fish_hash,
+            public_address,
+            name,
+            next_message_id: 0,
+            client_id: None,
+            xn: None,
+            target: [0; 32],
+            accepted_shares: 0,
+            rejected_shares: 0,
+        }
+    }
+
+    /// The encoded subscribe message, to send when connecting to the pool.
+    pub fn subscribe(&mut self) -> String {
+        self.encode(ClientMessage::Subscribe {
+            version: STRATUM_VERSION,
+            name: self.name.clone(),
+            public_address: self.public_address.clone(),
+            agent: None,
+        })
+    }
+
+    /// Update the work of the thread pool according to a message of the pool.
+    pub fn handle_message(&mut self, message: &ServerMessage) -> Result<(), IronfishError> {
+        match message {
+            ServerMessage::Subscribed { client_id, xn } => {
+                if xn.is_empty() || xn.len() >= 8 {
+                    return Err(IronfishError::new(IronfishErrorKind::InvalidData)
+                        .with_context("mining.subscribed.xn"));
+                }
+                self.client_id = Some(*client_id);
+                self.xn = Some(xn.clone());
+            }
+            ServerMessage::SetTarget { target } => {
+                self.target = *target;
+            }
+            ServerMessage::Notify {
+                mining_request_id,
+                header,
+            } => {
+                let xn = self.xn.as_ref().ok_or_else(|| {
+                    IronfishError::new(IronfishErrorKind::InvalidData).with_context("mining.notify")
+                })?;
+                let offset = if self.fish_hash {
+                    FISH_HASH_RANDOMNESS_OFFSET
+                } else {
+                    0
+                };
+                if header.len() < offset + 8 {
+                    return Err(IronfishError::new(IronfishErrorKind::InvalidData)
+                        .with_context("mining.notify.header"));
+                }
+
+                let mut header_bytes = header.clone();
+                header_bytes[offset..offset + xn.len()].copy_from_slice(xn);
+                self.thread_pool.new_work(
+                    &header_bytes,
+                    &self.target,
+                    None,
+                    *mining_request_id,
+                    self.fish_hash,
+                    xn.len() as u8,
+                );
+            }
+            ServerMessage::WaitForWork => self.thread_pool.pause(),
+            ServerMessage::Submitted { result, .. } => {
+                if *result {
+                    self.accepted_shares += 1;
+                } else {
+                    self.rejected_shares += 1;
+                }
+            }
+            ServerMessage::Disconnect { .. } | ServerMessage::Error { .. } => {}
+        }
+
+        Ok(())
+    }
+
+    /// The encoded submit message for a block found by the thread pool, if
+    /// any.
+    pub fn poll(&mut self) -> Option<String> {
+        let (randomness, mining_request_id) = self.thread_pool.get_found_block()?;
+
+        Some(self.encode(ClientMessage::Submit {
+            mining_request_id,
+            randomness,
+        }))
+    }
+
+    /// Subscribe to the pool at the other end of `stream` and mine its work,
+    /// until the pool disconnects or `stop` is set. The thread pool is
+    /// stopped before returning.
+    pub fn run(&mut self, stream: TcpStream, stop: &AtomicBool) -> Result<(), IronfishError> {
+        let result = self.run_connection(stream, stop);
+        self.thread_pool.stop();
+        result
+    }
+
+    fn run_connection(
+        &mut self,
+        stream: TcpStream,
+        stop: &AtomicBool,
+    ) -> Result<(), IronfishError> {
+        stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        writer.write_all(self.subscribe().as_bytes())?;
+
+        // Bytes are kept across timeouts until a whole line is read
+        let mut line = Vec::new();
+        while !stop.load(Ordering::Relaxed) {
+            match reader.read_until(b'\n', &mut line) {
+                // The pool closed the connection
+                Ok(0) => return Ok(()),
+                Ok(_) if line.ends_with(b"\n") => {
+                    let text = std::str::from_utf8(&line).map_err(invalid_data)?;
+                    let (_, message) = ServerMessage::decode(text)?;
+                    line.clear();
+
+                    self.handle_message(&message)?;
+                    if let ServerMessage::Disconnect { .. } = message {
+                        return Ok(());
+                    }
+                }
+                // The connection was closed in the middle of a message
+                Ok(_) => return Ok(()),
+                Err(e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            if let Some(submit) = self.poll() {
+                writer.write_all(submit.as_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn client_id(&self) -> Option<u64> {
+        self.client_id
+    }
+
+    pub fn accepted_shares(&self) -> u64 {
+        self.accepted_shares
+    }
+
+    pub fn rejected_shares(&self) -> u64 {
+        self.rejected_shares
+    }
+
+    fn encode(&mut self, message: ClientMessage) -> String {
+        let id = self.next_message_id;
+        self.next_message_id += 1;
+        message.encode(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::{TcpListener, TcpStream},
+        sync::atomic::AtomicBool,
+        thread,
+    };
+
+    use super::{ClientMessage, ServerMessage, StratumMiner, STRATUM_VERSION};
+    use crate::{
+        errors::IronfishErrorKind, mining::mine::bytes_lte, mining::threadpool::ThreadPool,
+    };
+
+    #[test]
+    fn test_message_round_trip() {
+        let client_messages = [
+            ClientMessage::Subscribe {
+                version: STRATUM_VERSION,
+                name: Some("miner".to_string()),
+                public_address: "ab".repeat(32),
+                agent: None,
+            },
+            ClientMessage::Submit {
+                mining_request_id: 3,
+                randomness: 0x0102_0000_0000_00ff,
+            },
+        ];
+        for (id, message) in client_messages.into_iter().enumerate() {
+            let line = message.encode(id as u64);
+            assert!(line.ends_with('\n'));
+            assert_eq!(ClientMessage::decode(&line).unwrap(), (id as u64, message));
+        }
+
+        let server_messages = [
+            ServerMessage::Subscribed {
+                client_id: 4,
+                xn: vec![0xab, 0xcd],
+            },
+            ServerMessage::SetTarget { target: [7; 32] },
+            ServerMessage::Notify {
+                mining_request_id: 5,
+                header: vec![1; 180],
+            },
+            ServerMessage::WaitForWork,
+            ServerMessage::Submitted {
+                id: 6,
+                result: false,
+                message: Some("stale".to_string()),
+            },
+            ServerMessage::Disconnect {
+                reason: Some("bad_version".to_string()),
+                version_expected: Some(2),
+                banned_until: None,
+                message: None,
+            },
+            ServerMessage::Error {
+                id: 8,
+                message: "invalid".to_string(),
+            },
+        ];
+        for (id, message) in server_messages.into_iter().enumerate() {
+            let line = message.encode(id as u64);
+            assert_eq!(ServerMessage::decode(&line).unwrap(), (id as u64, message));
+        }
+    }
+
+    #[test]
+    fn test_message_format() {
+        let line = ClientMessage::Submit {
+            mining_request_id: 3,
+            randomness: 0xab00_0000_0000_0001,
+        }
+        .encode(1);
+        assert_eq!(
+            line,
+            "{\"id\":1,\"method\":\"mining.submit\",\"body\":{\"miningRequestId\":3,\"randomness\":\"ab00000000000001\"}}\n"
+        );
+
+        let (id, message) =
+            ServerMessage::decode("{\"id\":2,\"method\":\"mining.disconnect\"}").unwrap();
+        assert_eq!(id, 2);
+        assert!(matches!(
+            message,
+            ServerMessage::Disconnect { reason: None, .. }
+        ));
+
+        let error = ServerMessage::decode(
+            "{\"id\":2,\"method\":\"mining.set_target\",\"body\":{\"target\":\"00\"}}",
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, IronfishErrorKind::HexError);
+        assert_eq!(
+            error.context_path().as_deref(),
+            Some("mining.set_target.target")
+        );
+
+        let error = ServerMessage::decode("{\"id\":2,\"method\":\"mining.unknown\"}").unwrap_err();
+        assert_eq!(error.kind, IronfishErrorKind::InvalidData);
+        assert_eq!(error.context_path().as_deref(), Some("mining.unknown"));
+    }
+
+    /// A pool that sends a header to mine, and checks the submitted share.
+    #[test]
+    fn test_stratum_miner_mock_pool() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let public_address = "cd".repeat(32);
+        let xn = vec![0xab];
+        let header = (0..180).map(|i| i as u8).collect::<Vec<_>>();
+        // About one hash in 16 meets the target
+        let mut target = [0xff; 32];
+        target[0] = 0x0f;
+
+        let pool = {
+            let public_address = public_address.clone();
+            let header = header.clone();
+            thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = stream.try_clone().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+
+                reader.read_line(&mut line).unwrap();
+                let (_, subscribe) = ClientMessage::decode(&line).unwrap();
+                assert_eq!(
+                    subscribe,
+                    ClientMessage::Subscribe {
+                        version: STRATUM_VERSION,
+                        name: None,
+                        public_address,
+                        agent: None,
+                    }
+                );
+
+                for (id, message) in [
+                    ServerMessage::Subscribed {
+                        client_id: 1,
+                        xn: xn.clone(),
+                    },
+                    ServerMessage::SetTarget { target },
+                    ServerMessage::Notify {
+                        mining_request_id: 7,
+                        header: header.clone(),
+                    },
+                ]
+                .iter()
+                .enumerate()
+                {
+                    writer
+                        .write_all(message.encode(id as u64).as_bytes())
+                        .unwrap();
+                }
+
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                let (submit_id, submit) = ClientMessage::decode(&line).unwrap();
+                let ClientMessage::Submit {
+                    mining_request_id,
+                    randomness,
+                } = submit
+                else {
+                    panic!("expected a submit, got {:?}", submit);
+                };
+                assert_eq!(mining_request_id, 7);
+                assert_eq!(randomness.to_be_bytes()[0], xn[0]);
+
+                let mut mined_header = header.clone();
+                mined_header[..8].copy_from_slice(&randomness.to_be_bytes());
+                assert!(bytes_lte(blake3::hash(&mined_header).as_bytes(), &target));
+
+                for (id, message) in [
+                    ServerMessage::Submitted {
+                        id: submit_id,
+                        result: true,
+                        message: None,
+                    },
+                    ServerMessage::Disconnect {
+                        reason: None,
+                        version_expected: None,
+                        banned_until: None,
+                        message: None,
+                    },
+                ]
+                .iter()
+                .enumerate()
+                {
+                    writer
+                        .write_all(message.encode(id as u64 + 3).as_bytes())
+                        .unwrap();
+                }
+            })
+        };
+
+        let thread_pool = ThreadPool::new(1, 100, true, false, false);
+        let mut miner = StratumMiner::new(thread_pool, false, public_address, None);
+        let stream = TcpStream::connect(address).unwrap();
+        miner.run(stream, &AtomicBool::new(false)).unwrap();
+        pool.join().unwrap();
+
+        assert_eq!(miner.client_id(), Some(1));
+        assert_eq!(miner.accepted_shares(), 1);
+        assert_eq!(miner.rejected_shares(), 0);
+    }
+}
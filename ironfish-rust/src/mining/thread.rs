@@ -3,70 +3,76 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 use std::{
     collections::VecDeque,
-    sync::mpsc::{self, Receiver, SendError, Sender},
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, Receiver, SendError, Sender},
+        Arc,
+    },
     thread,
 };
 
-use super::mine;
-use fish_hash::Context;
+use super::{
+    algorithm::{AlgorithmRegistry, MiningAlgorithm},
+    mine::bytes_lte,
+    stats::MiningCounters,
+    threadpool::Share,
+};
 
 #[derive(Debug)]
 pub(super) enum Command {
     NewWork(
-        Vec<u8>, // header bytes
-        Vec<u8>, // target
-        u32,     // mining request id
-        bool,    // use fish hash
-        u8,      // xn length
+        Vec<u8>,         // header bytes
+        Vec<u8>,         // target
+        Option<Vec<u8>>, // share target
+        u32,             // mining request id
+        usize,           // algorithm index
+        u8,              // xn length
     ),
     Stop,
     Pause,
 }
 
-pub(super) struct FishHashOptions {
-    pub(super) enabled: bool,
-    pub(super) full_context: bool,
-}
-
 pub(super) struct Thread {
     command_channel: Sender<Command>,
 }
 
+/// The channels through which a thread reports its results to the pool.
+#[derive(Clone)]
+pub(super) struct ResultChannels {
+    pub(super) block_found: Sender<(u64, u32)>,
+    pub(super) share_found: Sender<Share>,
+    pub(super) hash_rate: Sender<u32>,
+}
+
 impl Thread {
     pub(super) fn new(
         id: u64,
-        block_found_channel: Sender<(u64, u32)>,
-        hash_rate_channel: Sender<u32>,
+        channels: ResultChannels,
+        counters: Arc<MiningCounters>,
         pool_size: usize,
         batch_size: u32,
         pause_on_success: bool,
-        fish_hash_options: FishHashOptions,
+        algorithms: AlgorithmRegistry,
     ) -> Self {
         let (work_sender, work_receiver) = mpsc::channel::<Command>();
 
         thread::Builder::new()
             .name(id.to_string())
             .spawn(move || {
-                let mut fish_hash_context = if fish_hash_options.enabled {
-                    Some(fish_hash::Context::new(
-                        fish_hash_options.full_context,
-                        None,
-                    ))
-                } else {
-                    None
-                };
+                let mut algorithms = algorithms.create_all();
+                counters.threads_ready.fetch_add(1, Ordering::Relaxed);
 
                 process_commands(
                     work_receiver,
-                    block_found_channel,
-                    hash_rate_channel,
+                    channels,
+                    &counters,
                     NonceOptions {
                         start: id,
                         step_size: pool_size,
                         default_batch_size: batch_size as u64,
                     },
                     pause_on_success,
-                    &mut fish_hash_context,
+                    &mut algorithms,
                 )
             })
             .unwrap();
@@ -80,15 +86,17 @@ impl Thread {
         &self,
         header_bytes: Vec<u8>,
         target: Vec<u8>,
+        share_target: Option<Vec<u8>>,
         mining_request_id: u32,
-        use_fish_hash: bool,
+        algorithm_index: usize,
         xn_length: u8,
     ) -> Result<(), SendError<Command>> {
         self.command_channel.send(Command::NewWork(
             header_bytes,
             target,
+            share_target,
             mining_request_id,
-            use_fish_hash,
+            algorithm_index,
             xn_length,
         ))
     }
@@ -110,11 +118,11 @@ struct NonceOptions {
 
 fn process_commands(
     work_receiver: Receiver<Command>,
-    block_found_channel: Sender<(u64, u32)>,
-    hash_rate_channel: Sender<u32>,
+    channels: ResultChannels,
+    counters: &MiningCounters,
     nonce_options: NonceOptions,
     pause_on_success: bool,
-    fish_hash_context: &mut Option<Context>,
+    algorithms: &mut [Box<dyn MiningAlgorithm>],
 ) {
     let start = nonce_options.start;
     let step_size = nonce_options.step_size;
@@ -132,10 +140,18 @@ fn process_commands(
             Command::NewWork(
                 mut header_bytes,
                 target,
+                share_target,
                 mining_request_id,
-                use_fish_hash,
+                algorithm_index,
                 xn_length,
             ) => {
+                let algorithm = &mut algorithms[algorithm_index];
+                // Search for hashes that meet the easier of the two targets,
+                // and check which of them also meet the block target
+                let search_target = match &share_target {
+                    Some(share_target) if bytes_lte(&target, share_target) => share_target,
+                    _ => &target,
+                };
                 let search_space = 2_u64.pow(64 - (xn_length as u32 * 8)) - 1;
                 let mut batch_start = start;
                 loop {
@@ -146,33 +162,30 @@ fn process_commands(
                         remaining_search_space
                     };
 
-                    let match_found = match use_fish_hash {
-                        false => mine::mine_batch_blake3(
-                            &mut header_bytes,
-                            xn_length,
-                            &target,
-                            batch_start,
-                            step_size,
-                            batch_size,
-                        ),
-                        true => mine::mine_batch_fish_hash(
-                            fish_hash_context.as_mut().unwrap(),
-                            &mut header_bytes,
-                            xn_length,
-                            &target,
-                            batch_start,
-                            step_size,
-                            batch_size,
-                        ),
-                    };
+                    let match_found = algorithm.mine_batch(
+                        &mut header_bytes,
+                        xn_length,
+                        search_target,
+                        batch_start,
+                        step_size,
+                        batch_size,
+                    );
 
-                    // Submit amount of work done
+                    // Submit amount of work done. The randomness of a match
+                    // includes the extra nonce, which is not part of the
+                    // search space.
                     let work_done = match match_found {
-                        Some(randomness) => randomness - batch_start,
+                        Some(randomness) => {
+                            (randomness & search_space) - batch_start + step_size as u64
+                        }
                         None => batch_size,
                     };
-                    hash_rate_channel
-                        .send((work_done / step_size as u64) as u32)
+                    let hashes = work_done / step_size as u64;
+                    // The first nonce of a thread is its index in the pool
+                    counters.add_hashes(start as usize, hashes);
+                    channels
+                        .hash_rate
+                        .send(u32::try_from(hashes).unwrap_or(u32::MAX))
                         .unwrap();
 
                     // New command received, this work is now stale, stop working so we can start on new work
@@ -181,21 +194,55 @@ fn process_commands(
                         break;
                     }
 
-                    if let Some(randomness) = match_found {
-                        if let Err(e) = block_found_channel.send((randomness, mining_request_id)) {
-                            panic!("Error sending found block: {:?}", e);
-                        }
+                    let next_batch_start = match match_found {
+                        Some(randomness) => {
+                            let hash = algorithm.hash(&header_bytes);
+
+                            if let Some(share_target) = &share_target {
+                                if bytes_lte(&hash, share_target) {
+                                    counters.shares_found.fetch_add(1, Ordering::Relaxed);
+                                    let share = Share {
+                                        randomness,
+                                        mining_request_id,
+                                        hash,
+                                    };
+                                    if let Err(e) = channels.share_found.send(share) {
+                                        panic!("Error sending found share: {:?}", e);
+                                    }
+                                }
+                            }
+
+                            if bytes_lte(&hash, &target) {
+                                counters.blocks_found.fetch_add(1, Ordering::Relaxed);
+                                if let Err(e) =
+                                    channels.block_found.send((randomness, mining_request_id))
+                                {
+                                    panic!("Error sending found block: {:?}", e);
+                                }
 
-                        if pause_on_success {
-                            break;
+                                if pause_on_success {
+                                    break;
+                                }
+                            }
+
+                            // Continue after the match, to find the other
+                            // matches of the batch
+                            (randomness & search_space) + step_size as u64
                         }
-                    }
+                        None => {
+                            if remaining_search_space < default_batch_size {
+                                // miner has exhausted its search space, stop mining
+                                break;
+                            }
+                            batch_start + batch_size + step_size as u64
+                                - (batch_size % step_size as u64)
+                        }
+                    };
 
-                    if remaining_search_space < default_batch_size {
-                        // miner has exhausted its search space, stop mining
+                    if next_batch_start > search_space {
                         break;
                     }
-                    batch_start += batch_size + step_size as u64 - (batch_size % step_size as u64);
+                    batch_start = next_batch_start;
                 }
             }
             Command::Pause => {
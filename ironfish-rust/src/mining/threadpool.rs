@@ -1,15 +1,43 @@
 /* This Source Code Form is subject to the terms of the Mozilla Public
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
-use std::sync::mpsc::{self, Receiver};
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    time::Instant,
+};
 
-use super::thread::{FishHashOptions, Thread};
+use super::{
+    algorithm::{AlgorithmRegistry, BLAKE3, FISH_HASH},
+    stats::{HashRateWindow, MiningCounters, MiningStats},
+    thread::{ResultChannels, Thread},
+};
+use crate::errors::{IronfishError, IronfishErrorKind};
+
+/// A randomness whose hash meets the share target of the work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Share {
+    /// The randomness, including the extra nonce
+    pub randomness: u64,
+    pub mining_request_id: u32,
+    pub hash: [u8; 32],
+}
 
 pub struct ThreadPool {
     threads: Vec<Thread>,
+    algorithms: AlgorithmRegistry,
     block_found_receiver: Receiver<(u64, u32)>,
+    share_found_receiver: Receiver<Share>,
     hash_rate_receiver: Receiver<u32>,
     mining_request_id: u32,
+    counters: Arc<MiningCounters>,
+    stale_discarded: Cell<u64>,
+    started_at: Instant,
+    hash_rate_window: HashRateWindow,
 }
 
 impl ThreadPool {
@@ -19,45 +47,109 @@ impl ThreadPool {
         pause_on_success: bool,
         use_fish_hash: bool,
         fish_hash_full_context: bool,
+    ) -> Self {
+        Self::with_algorithms(
+            thread_count,
+            batch_size,
+            pause_on_success,
+            AlgorithmRegistry::with_defaults(use_fish_hash, fish_hash_full_context),
+        )
+    }
+
+    /// Create a pool whose threads can mine with any of the given algorithms.
+    /// Each thread creates its own instance of every algorithm when it
+    /// starts.
+    pub fn with_algorithms(
+        thread_count: usize,
+        batch_size: u32,
+        pause_on_success: bool,
+        algorithms: AlgorithmRegistry,
     ) -> Self {
         let (block_found_channel, block_found_receiver) = mpsc::channel::<(u64, u32)>();
 
+        let (share_found_channel, share_found_receiver) = mpsc::channel::<Share>();
+
         let (hash_rate_channel, hash_rate_receiver) = mpsc::channel::<u32>();
 
+        let channels = ResultChannels {
+            block_found: block_found_channel,
+            share_found: share_found_channel,
+            hash_rate: hash_rate_channel,
+        };
+
+        let counters = Arc::new(MiningCounters::new(thread_count));
+        let started_at = Instant::now();
+
         let mut threads = Vec::with_capacity(thread_count);
         for id in 0..thread_count {
-            let fish_hash_options = FishHashOptions {
-                enabled: use_fish_hash,
-                full_context: fish_hash_full_context,
-            };
-
             threads.push(Thread::new(
                 id as u64,
-                block_found_channel.clone(),
-                hash_rate_channel.clone(),
+                channels.clone(),
+                counters.clone(),
                 thread_count,
                 batch_size,
                 pause_on_success,
-                fish_hash_options,
+                algorithms.clone(),
             ));
         }
 
         ThreadPool {
             threads,
+            algorithms,
             block_found_receiver,
+            share_found_receiver,
             hash_rate_receiver,
             mining_request_id: 0,
+            counters,
+            stale_discarded: Cell::new(0),
+            started_at,
+            hash_rate_window: HashRateWindow::new(started_at),
         }
     }
 
+    /// Start mining the header, replacing any previous work. Randomness
+    /// values whose hash meets `target` are returned by
+    /// [`ThreadPool::get_found_block`], and, if there is a `share_target`,
+    /// the ones whose hash meets it are returned by
+    /// [`ThreadPool::get_found_share`].
     pub fn new_work(
         &mut self,
         header_bytes: &[u8],
         target: &[u8],
+        share_target: Option<&[u8]>,
         mining_request_id: u32,
         fish_hash: bool,
         xn_length: u8,
     ) {
+        let algorithm = if fish_hash { FISH_HASH } else { BLAKE3 };
+
+        self.new_work_with_algorithm(
+            header_bytes,
+            target,
+            share_target,
+            mining_request_id,
+            algorithm,
+            xn_length,
+        )
+        .expect("the algorithm should be enabled in the thread pool")
+    }
+
+    /// Start mining the header with the registered algorithm of the given
+    /// name, as in [`ThreadPool::new_work`].
+    pub fn new_work_with_algorithm(
+        &mut self,
+        header_bytes: &[u8],
+        target: &[u8],
+        share_target: Option<&[u8]>,
+        mining_request_id: u32,
+        algorithm: &str,
+        xn_length: u8,
+    ) -> Result<(), IronfishError> {
+        let algorithm_index = self
+            .algorithms
+            .index_of(algorithm)
+            .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidMiningAlgorithm))?;
+
         self.mining_request_id = mining_request_id;
 
         for thread in self.threads.iter() {
@@ -65,12 +157,19 @@ impl ThreadPool {
                 .new_work(
                     header_bytes.to_vec(),
                     target.to_vec(),
+                    share_target.map(|share_target| share_target.to_vec()),
                     mining_request_id,
-                    fish_hash,
+                    algorithm_index,
                     xn_length,
                 )
                 .unwrap();
         }
+
+        Ok(())
+    }
+
+    pub fn algorithms(&self) -> &AlgorithmRegistry {
+        &self.algorithms
     }
 
     pub fn stop(&self) {
@@ -89,6 +188,7 @@ impl ThreadPool {
         if let Ok((randomness, mining_request_id)) = self.block_found_receiver.try_recv() {
             // Stale work
             if mining_request_id != self.mining_request_id {
+                self.discard_stale();
                 return None;
             }
             return Some((randomness, mining_request_id));
@@ -96,11 +196,204 @@ impl ThreadPool {
         None
     }
 
+    pub fn get_found_share(&self) -> Option<Share> {
+        if let Ok(share) = self.share_found_receiver.try_recv() {
+            // Stale work
+            if share.mining_request_id != self.mining_request_id {
+                self.discard_stale();
+                return None;
+            }
+            return Some(share);
+        }
+        None
+    }
+
     pub fn get_hash_rate_submission(&self) -> u32 {
         let mut total_hash_rate = 0;
         for hash_rate in self.hash_rate_receiver.try_iter() {
-            total_hash_rate += hash_rate
+            total_hash_rate = u32::saturating_add(total_hash_rate, hash_rate)
         }
         total_hash_rate
     }
+
+    /// A snapshot of the work done since the thread pool was created. The
+    /// hash rate is averaged over the time since the oldest call to this
+    /// function within [`HASH_RATE_WINDOW`](super::stats::HASH_RATE_WINDOW).
+    pub fn stats(&mut self) -> MiningStats {
+        let now = Instant::now();
+        let thread_hashes = self.counters.thread_hashes();
+        let total_hashes = thread_hashes
+            .iter()
+            .fold(0u64, |total, hashes| total.saturating_add(*hashes));
+
+        MiningStats {
+            elapsed: now.saturating_duration_since(self.started_at),
+            thread_hashes,
+            hash_rate: self.hash_rate_window.sample(now, total_hashes),
+            blocks_found: self.counters.blocks_found.load(Ordering::Relaxed),
+            shares_found: self.counters.shares_found.load(Ordering::Relaxed),
+            stale_discarded: self.stale_discarded.get(),
+            threads_ready: self.counters.threads_ready.load(Ordering::Relaxed),
+        }
+    }
+
+    fn discard_stale(&self) {
+        self.stale_discarded
+            .set(self.stale_discarded.get().saturating_add(1));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{thread, time::Duration};
+
+    use super::ThreadPool;
+    use crate::{
+        errors::IronfishErrorKind,
+        mining::{
+            algorithm::{AlgorithmRegistry, MiningAlgorithm},
+            mine::bytes_lte,
+        },
+    };
+
+    /// Matches the randomness 5, whatever the header and target.
+    struct MatchFive;
+
+    impl MiningAlgorithm for MatchFive {
+        fn mine_batch(
+            &mut self,
+            _header_bytes: &mut [u8],
+            _xn_length: u8,
+            _target: &[u8],
+            start: u64,
+            step_size: usize,
+            batch_size: u64,
+        ) -> Option<u64> {
+            (start..=start + batch_size)
+                .step_by(step_size)
+                .find(|randomness| *randomness == 5)
+        }
+
+        fn hash(&mut self, _header_bytes: &[u8]) -> [u8; 32] {
+            [0; 32]
+        }
+    }
+
+    #[test]
+    fn test_thread_pool_with_algorithms() {
+        let mut algorithms = AlgorithmRegistry::with_defaults(false, false);
+        algorithms.register("five", || Box::new(MatchFive));
+        let mut pool = ThreadPool::with_algorithms(2, 10, true, algorithms);
+
+        let error = pool
+            .new_work_with_algorithm(&[0; 180], &[0; 32], None, 1, "unknown", 0)
+            .unwrap_err();
+        assert_eq!(error.kind, IronfishErrorKind::InvalidMiningAlgorithm);
+
+        pool.new_work_with_algorithm(&[0; 180], &[0; 32], None, 2, "five", 1)
+            .unwrap();
+
+        let mut found = None;
+        for _ in 0..100 {
+            found = pool.get_found_block();
+            if found.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(found, Some((5, 2)));
+
+        pool.stop();
+    }
+
+    #[test]
+    fn test_thread_pool_shares() {
+        let mut pool = ThreadPool::new(2, 100, false, false, false);
+        let header = (0..180).map(|i| i as u8).collect::<Vec<_>>();
+        // No hash meets the block target, and about one in 16 meets the
+        // share target
+        let target = [0; 32];
+        let mut share_target = [0xff; 32];
+        share_target[0] = 0x0f;
+
+        pool.new_work(&header, &target, Some(&share_target), 1, false, 1);
+
+        let mut shares = Vec::new();
+        for _ in 0..100 {
+            while let Some(share) = pool.get_found_share() {
+                shares.push(share);
+            }
+            if shares.len() >= 3 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(shares.len() >= 3);
+        assert_eq!(pool.get_found_block(), None);
+
+        for share in shares {
+            assert_eq!(share.mining_request_id, 1);
+
+            let mut mined_header = header.clone();
+            mined_header[..8].copy_from_slice(&share.randomness.to_be_bytes());
+            assert_eq!(share.hash, *blake3::hash(&mined_header).as_bytes());
+            assert!(bytes_lte(&share.hash, &share_target));
+        }
+
+        // Shares of the previous work are stale, and there are no shares
+        // without a share target
+        pool.new_work(&header, &target, None, 2, false, 1);
+        thread::sleep(Duration::from_millis(50));
+        for _ in 0..100 {
+            assert_eq!(pool.get_found_share(), None);
+        }
+
+        pool.stop();
+    }
+
+    #[test]
+    fn test_thread_pool_stats() {
+        let mut pool = ThreadPool::new(2, 100, false, false, false);
+        let header = (0..180).map(|i| i as u8).collect::<Vec<_>>();
+        let target = [0; 32];
+        let mut share_target = [0xff; 32];
+        share_target[0] = 0x0f;
+
+        let stats = pool.stats();
+        assert_eq!(stats.thread_hashes.len(), 2);
+        assert_eq!(stats.total_hashes(), 0);
+
+        pool.new_work(&header, &target, Some(&share_target), 1, false, 1);
+
+        let mut stats = pool.stats();
+        for _ in 0..100 {
+            stats = pool.stats();
+            if stats.shares_found >= 3 && stats.thread_hashes.iter().all(|h| *h > 0) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(stats.threads_ready, 2);
+        assert_eq!(stats.ready_progress(), 1.0);
+        assert!(stats.thread_hashes.iter().all(|hashes| *hashes > 0));
+        assert!(stats.shares_found >= 3);
+        assert_eq!(stats.blocks_found, 0);
+        assert!(stats.elapsed > Duration::ZERO);
+
+        // Shares that were not read before the new work are discarded. There
+        // are no new shares without a share target.
+        pool.new_work(&header, &target, None, 2, false, 1);
+        thread::sleep(Duration::from_millis(50));
+        let shares_found = pool.stats().shares_found;
+        for _ in 0..shares_found {
+            assert_eq!(pool.get_found_share(), None);
+        }
+
+        let stats = pool.stats();
+        assert_eq!(stats.shares_found, shares_found);
+        assert_eq!(stats.stale_discarded, stats.shares_found);
+        assert!(stats.hash_rate > 0.0);
+
+        pool.stop();
+    }
 }
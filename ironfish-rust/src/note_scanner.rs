@@ -0,0 +1,252 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Trial decryption of many notes against many view keys at once.
+//!
+//! Wallets need to attempt to decrypt every note in a block with every
+//! account they are tracking. [`NoteScanner`] does this in a single call:
+//! the Diffie-Hellman shared secrets for all the incoming view keys are
+//! computed together for each note, and notes are processed in parallel on
+//! the current rayon thread pool.
+
+use crate::{
+    keys::{shared_secrets, IncomingViewKey, OutgoingViewKey},
+    merkle_note::MerkleNote,
+    note::Note,
+};
+use rayon::prelude::*;
+
+/// A note that was successfully decrypted by one of the keys of a
+/// [`NoteScanner`].
+#[derive(Clone, Debug)]
+pub struct NoteMatch {
+    /// Index of the note in the slice passed to the scanner.
+    pub note_index: usize,
+    /// Index of the key that decrypted the note, in the list of keys of the
+    /// same kind that the scanner was created with.
+    pub key_index: usize,
+    /// The decrypted note.
+    pub note: Note,
+}
+
+/// Notes found by [`NoteScanner::scan`], ordered by note index and then by
+/// key index.
+#[derive(Clone, Debug, Default)]
+pub struct ScanResult {
+    /// Notes decrypted with an incoming view key (notes received).
+    pub received: Vec<NoteMatch>,
+    /// Notes decrypted with an outgoing view key (notes sent).
+    pub sent: Vec<NoteMatch>,
+}
+
+/// Decrypts batches of notes with a fixed set of view keys.
+///
+/// Incoming view keys find the notes that were sent to their account, and
+/// outgoing view keys find the notes that were sent by their account.
+pub struct NoteScanner {
+    incoming_view_keys: Vec<IncomingViewKey>,
+    raw_incoming_view_keys: Vec<[u8; 32]>,
+    outgoing_view_keys: Vec<OutgoingViewKey>,
+}
+
+impl NoteScanner {
+    pub fn new(
+        incoming_view_keys: Vec<IncomingViewKey>,
+        outgoing_view_keys: Vec<OutgoingViewKey>,
+    ) -> Self {
+        let raw_incoming_view_keys = incoming_view_keys
+            .iter()
+            .map(|ivk| ivk.view_key.to_bytes())
+            .collect();
+
+        Self {
+            incoming_view_keys,
+            raw_incoming_view_keys,
+            outgoing_view_keys,
+        }
+    }
+
+    /// Attempt to decrypt every note with every incoming and outgoing view
+    /// key of this scanner.
+    pub fn scan(&self, notes: &[MerkleNote]) -> ScanResult {
+        let (received, sent) =
+            rayon::join(|| self.scan_incoming(notes), || self.scan_outgoing(notes));
+
+        ScanResult { received, sent }
+    }
+
+    /// Attempt to decrypt every note with every incoming view key of this
+    /// scanner. Matches are ordered by note index and then by key index.
+    pub fn scan_incoming(&self, notes: &[MerkleNote]) -> Vec<NoteMatch> {
+        if self.incoming_view_keys.is_empty() {
+            return vec![];
+        }
+
+        notes
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(note_index, merkle_note)| {
+                let shared_secrets = shared_secrets(
+                    &self.raw_incoming_view_keys,
+                    &merkle_note.ephemeral_public_key,
+                    &merkle_note.ephemeral_public_key,
+                );
+
+                self.incoming_view_keys
+                    .iter()
+                    .zip(shared_secrets)
+                    .enumerate()
+                    .filter_map(move |(key_index, (ivk, shared_secret))| {
+                        let note = Note::from_owner_encrypted(
+                            ivk,
+                            &shared_secret,
+                            &merkle_note.encrypted_note,
+                        )
+                        .ok()?;
+                        note.verify_commitment(merkle_note.note_commitment).ok()?;
+
+                        Some(NoteMatch {
+                            note_index,
+                            key_index,
+                            note,
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Attempt to decrypt every note with every outgoing view key of this
+    /// scanner. Matches are ordered by note index and then by key index.
+    pub fn scan_outgoing(&self, notes: &[MerkleNote]) -> Vec<NoteMatch> {
+        if self.outgoing_view_keys.is_empty() {
+            return vec![];
+        }
+
+        notes
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(|(note_index, merkle_note)| {
+                self.outgoing_view_keys
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(key_index, ovk)| {
+                        let note = merkle_note.decrypt_note_for_spender(ovk).ok()?;
+
+                        Some(NoteMatch {
+                            note_index,
+                            key_index,
+                            note,
+                        })
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::NoteScanner;
+    use crate::{
+        assets::asset_identifier::NATIVE_ASSET, keys::EphemeralKeyPair, note::Note, MerkleNote,
+        SaplingKey,
+    };
+    use ironfish_zkp::primitives::ValueCommitment;
+
+    fn make_merkle_note(sender: &SaplingKey, receiver: &SaplingKey, value: u64) -> MerkleNote {
+        let note = Note::new(
+            receiver.public_address(),
+            value,
+            "",
+            NATIVE_ASSET,
+            sender.public_address(),
+        );
+        let value_commitment = ValueCommitment::new(note.value, note.asset_generator());
+
+        MerkleNote::new(
+            sender.outgoing_view_key(),
+            &note,
+            &value_commitment,
+            &EphemeralKeyPair::new(),
+        )
+    }
+
+    #[test]
+    fn test_scan_incoming() {
+        let alice = SaplingKey::generate_key();
+        let bob = SaplingKey::generate_key();
+        let carol = SaplingKey::generate_key();
+
+        let notes = vec![
+            make_merkle_note(&carol, &bob, 1),
+            make_merkle_note(&carol, &carol, 2),
+            make_merkle_note(&carol, &alice, 3),
+            make_merkle_note(&alice, &bob, 4),
+        ];
+
+        let scanner = NoteScanner::new(
+            vec![
+                alice.incoming_view_key().clone(),
+                bob.incoming_view_key().clone(),
+            ],
+            vec![],
+        );
+        let matches = scanner.scan_incoming(&notes);
+
+        let found = matches
+            .iter()
+            .map(|m| (m.note_index, m.key_index, m.note.value()))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![(0, 1, 1), (2, 0, 3), (3, 1, 4)]);
+        assert_eq!(matches[1].note.owner(), alice.public_address());
+
+        assert!(scanner.scan_outgoing(&notes).is_empty());
+    }
+
+    #[test]
+    fn test_scan_outgoing() {
+        let alice = SaplingKey::generate_key();
+        let bob = SaplingKey::generate_key();
+
+        let notes = vec![
+            make_merkle_note(&alice, &bob, 1),
+            make_merkle_note(&bob, &alice, 2),
+            make_merkle_note(&alice, &alice, 3),
+        ];
+
+        let scanner = NoteScanner::new(
+            vec![alice.incoming_view_key().clone()],
+            vec![
+                bob.outgoing_view_key().clone(),
+                alice.outgoing_view_key().clone(),
+            ],
+        );
+        let result = scanner.scan(&notes);
+
+        let received = result
+            .received
+            .iter()
+            .map(|m| (m.note_index, m.key_index))
+            .collect::<Vec<_>>();
+        assert_eq!(received, vec![(1, 0), (2, 0)]);
+
+        let sent = result
+            .sent
+            .iter()
+            .map(|m| (m.note_index, m.key_index, m.note.value()))
+            .collect::<Vec<_>>();
+        assert_eq!(sent, vec![(0, 1, 1), (1, 0, 2), (2, 1, 3)]);
+    }
+
+    #[test]
+    fn test_scan_without_keys() {
+        let alice = SaplingKey::generate_key();
+        let notes = vec![make_merkle_note(&alice, &alice, 1)];
+
+        let scanner = NoteScanner::new(vec![], vec![]);
+        let result = scanner.scan(&notes);
+
+        assert!(result.received.is_empty());
+        assert!(result.sent.is_empty());
+    }
+}
@@ -0,0 +1,236 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Tracking of which notes of an account are spent.
+//!
+//! A spend does not reveal which note it spends, only its nullifier. The
+//! owner of a note can compute its nullifier from the note, its position in
+//! the note commitment tree and the account view key. [`NullifierIndex`]
+//! precomputes the nullifiers of the notes of an account, so that the
+//! nullifiers of incoming spends can be matched against them.
+
+use crate::{keys::ViewKey, note::Note, transaction::Transaction};
+use std::collections::{BTreeMap, HashMap};
+
+pub use ironfish_zkp::Nullifier;
+
+/// The effect of a nullifier on the notes of a [`NullifierIndex`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpendStatus {
+    /// The nullifier is not the nullifier of a note of the account.
+    Unknown,
+    /// The note at the given position was unspent, and is now spent.
+    Spent(u64),
+    /// The note at the given position was already spent.
+    DoubleSpend(u64),
+}
+
+struct IndexedNote {
+    note: Note,
+    nullifier: Nullifier,
+    spent: bool,
+}
+
+/// The notes of an account, indexed by nullifier.
+pub struct NullifierIndex {
+    view_key: ViewKey,
+    /// Notes by position in the note commitment tree
+    notes: BTreeMap<u64, IndexedNote>,
+    /// Positions of the notes by nullifier
+    positions: HashMap<[u8; 32], u64>,
+}
+
+impl NullifierIndex {
+    /// Create an empty index for the notes of the account with the given view
+    /// key.
+    pub fn new(view_key: ViewKey) -> Self {
+        Self {
+            view_key,
+            notes: BTreeMap::new(),
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Add an unspent note of the account at the given position in the note
+    /// commitment tree, and return its nullifier.
+    ///
+    /// Adding a note again at the same position replaces it and marks it as
+    /// unspent.
+    pub fn add_note(&mut self, note: Note, position: u64) -> Nullifier {
+        let nullifier = note.nullifier(&self.view_key, position);
+
+        if let Some(previous) = self.notes.insert(
+            position,
+            IndexedNote {
+                note,
+                nullifier,
+                spent: false,
+            },
+        ) {
+            self.positions.remove(&previous.nullifier.0);
+        }
+        self.positions.insert(nullifier.0, position);
+
+        nullifier
+    }
+
+    /// Remove the note at the given position, for example when the block that
+    /// created it is disconnected.
+    pub fn remove_note(&mut self, position: u64) -> Option<Note> {
+        let removed = self.notes.remove(&position)?;
+        self.positions.remove(&removed.nullifier.0);
+
+        Some(removed.note)
+    }
+
+    /// Mark the note with the given nullifier as spent.
+    pub fn mark_spent(&mut self, nullifier: &Nullifier) -> SpendStatus {
+        match self.find_mut(nullifier) {
+            None => SpendStatus::Unknown,
+            Some((position, note)) if note.spent => SpendStatus::DoubleSpend(position),
+            Some((position, note)) => {
+                note.spent = true;
+                SpendStatus::Spent(position)
+            }
+        }
+    }
+
+    /// Mark the note with the given nullifier as unspent, for example when
+    /// the transaction that spent it is removed from the chain. Returns the
+    /// position of the note if it was spent.
+    pub fn mark_unspent(&mut self, nullifier: &Nullifier) -> Option<u64> {
+        let (position, note) = self.find_mut(nullifier)?;
+
+        if note.spent {
+            note.spent = false;
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    /// Mark the notes spent by a transaction as spent. Returns the status of
+    /// each spend of the transaction, in order.
+    pub fn mark_transaction_spent(&mut self, transaction: &Transaction) -> Vec<SpendStatus> {
+        transaction
+            .spends()
+            .iter()
+            .map(|spend| self.mark_spent(&spend.nullifier()))
+            .collect()
+    }
+
+    /// Whether the note with the given nullifier is spent, or `None` if it
+    /// is not a note of the account.
+    pub fn is_spent(&self, nullifier: &Nullifier) -> Option<bool> {
+        let position = self.positions.get(&nullifier.0)?;
+
+        Some(self.notes[position].spent)
+    }
+
+    /// The nullifier of the note at the given position.
+    pub fn nullifier(&self, position: u64) -> Option<Nullifier> {
+        self.notes.get(&position).map(|note| note.nullifier)
+    }
+
+    /// The unspent notes of the account, with their positions, in order of
+    /// position.
+    pub fn unspent_notes(&self) -> impl Iterator<Item = (u64, &Note)> {
+        self.notes
+            .iter()
+            .filter(|(_, note)| !note.spent)
+            .map(|(position, note)| (*position, &note.note))
+    }
+
+    /// The spent notes of the account, with their positions, in order of
+    /// position.
+    pub fn spent_notes(&self) -> impl Iterator<Item = (u64, &Note)> {
+        self.notes
+            .iter()
+            .filter(|(_, note)| note.spent)
+            .map(|(position, note)| (*position, &note.note))
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    fn find_mut(&mut self, nullifier: &Nullifier) -> Option<(u64, &mut IndexedNote)> {
+        let position = *self.positions.get(&nullifier.0)?;
+        let note = self.notes.get_mut(&position)?;
+
+        Some((position, note))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Nullifier, NullifierIndex, SpendStatus};
+    use crate::{assets::asset_identifier::NATIVE_ASSET, note::Note, SaplingKey};
+
+    #[test]
+    fn test_nullifier_index() {
+        let key = SaplingKey::generate_key();
+        let other_key = SaplingKey::generate_key();
+        let mut index = NullifierIndex::new(key.view_key().clone());
+
+        let notes = [5, 10, 20].map(|value| {
+            Note::new(
+                key.public_address(),
+                value,
+                "",
+                NATIVE_ASSET,
+                other_key.public_address(),
+            )
+        });
+        let nullifiers = notes
+            .iter()
+            .enumerate()
+            .map(|(position, note)| index.add_note(note.clone(), position as u64))
+            .collect::<Vec<_>>();
+        assert_eq!(index.len(), 3);
+        assert_eq!(
+            nullifiers[1],
+            notes[1].nullifier(key.view_key(), 1),
+            "nullifiers should match the note nullifiers"
+        );
+        assert_eq!(index.nullifier(2), Some(nullifiers[2]));
+
+        assert_eq!(index.mark_spent(&nullifiers[1]), SpendStatus::Spent(1));
+        assert_eq!(
+            index.mark_spent(&nullifiers[1]),
+            SpendStatus::DoubleSpend(1)
+        );
+        assert_eq!(index.is_spent(&nullifiers[1]), Some(true));
+        assert_eq!(index.is_spent(&nullifiers[0]), Some(false));
+
+        // Nullifiers of notes of other accounts are not matched
+        let other_nullifier = notes[0].nullifier(other_key.view_key(), 0);
+        assert_eq!(index.mark_spent(&other_nullifier), SpendStatus::Unknown);
+        assert_eq!(index.is_spent(&other_nullifier), None);
+        assert_eq!(index.mark_spent(&Nullifier([0; 32])), SpendStatus::Unknown);
+
+        let unspent = index
+            .unspent_notes()
+            .map(|(position, note)| (position, note.value()))
+            .collect::<Vec<_>>();
+        assert_eq!(unspent, vec![(0, 5), (2, 20)]);
+        let spent = index
+            .spent_notes()
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        assert_eq!(spent, vec![1]);
+
+        assert_eq!(index.mark_unspent(&nullifiers[1]), Some(1));
+        assert_eq!(index.mark_unspent(&nullifiers[1]), None);
+        assert_eq!(index.mark_spent(&nullifiers[1]), SpendStatus::Spent(1));
+
+        assert!(index.remove_note(1).is_some());
+        assert_eq!(index.mark_spent(&nullifiers[1]), SpendStatus::Unknown);
+        assert_eq!(index.len(), 2);
+    }
+}
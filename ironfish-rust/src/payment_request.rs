@@ -0,0 +1,343 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Payment requests, encoded as `ironfish:` URIs.
+//!
+//! A payment request asks for an amount of an asset to be sent to an address,
+//! with an optional memo and an optional expiration sequence after which the
+//! request should no longer be paid. The format follows
+//! [BIP-21](https://github.com/bitcoin/bips/blob/master/bip-0021.mediawiki):
+//!
+//! ```text
+//! ironfish:<address>?amount=<amount>[&asset=<asset id>][&memo=<memo>][&expiration=<sequence>]
+//! ```
+//!
+//! * `address` is the hex encoded public address of the recipient.
+//! * `amount` is the value of the payment, in the smallest unit of the asset.
+//! * `asset` is the hex encoded asset identifier. Defaults to the native asset.
+//! * `memo` is the percent-encoded memo, at most 32 bytes once decoded.
+//! * `expiration` is the block sequence after which the request expires.
+//!
+//! As in BIP-21, unknown parameters are ignored, unless their name starts
+//! with `req-`, in which case the request is rejected.
+
+use crate::{
+    assets::asset_identifier::{AssetIdentifier, NATIVE_ASSET},
+    errors::{IronfishError, IronfishErrorKind},
+    note::{Memo, Note, MEMO_SIZE},
+    serializing::{bytes_to_hex, hex_to_bytes},
+    PublicAddress,
+};
+use std::{fmt, str::FromStr};
+
+pub const PAYMENT_REQUEST_SCHEME: &str = "ironfish";
+
+/// A request for a payment of `amount` of `asset_id` to `recipient`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaymentRequest {
+    pub recipient: PublicAddress,
+    pub asset_id: AssetIdentifier,
+    pub amount: u64,
+    pub memo: Memo,
+    /// The block sequence after which the request should no longer be paid.
+    pub expiration: Option<u32>,
+}
+
+impl PaymentRequest {
+    /// Request `amount` of the native asset, without a memo or expiration.
+    pub fn new(recipient: PublicAddress, amount: u64) -> Self {
+        PaymentRequest {
+            recipient,
+            asset_id: NATIVE_ASSET,
+            amount,
+            memo: Memo::default(),
+            expiration: None,
+        }
+    }
+
+    /// Create the note that pays this request, to be added to a transaction
+    /// with [`crate::transaction::ProposedTransaction::add_output`].
+    pub fn to_note(&self, sender: PublicAddress) -> Note {
+        Note::new(
+            self.recipient,
+            self.amount,
+            self.memo,
+            self.asset_id,
+            sender,
+        )
+    }
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}?amount={}",
+            PAYMENT_REQUEST_SCHEME,
+            self.recipient.hex_public_address(),
+            self.amount
+        )?;
+
+        if self.asset_id != NATIVE_ASSET {
+            write!(f, "&asset={}", bytes_to_hex(self.asset_id.as_bytes()))?;
+        }
+
+        // Memos are padded with zeros, which do not need to be encoded
+        let memo_length = self
+            .memo
+            .0
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |position| position + 1);
+        if memo_length > 0 {
+            write!(f, "&memo={}", percent_encode(&self.memo.0[..memo_length]))?;
+        }
+
+        if let Some(expiration) = self.expiration {
+            write!(f, "&expiration={}", expiration)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = IronfishError;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = uri
+            .split_once(':')
+            .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidPaymentRequest))?;
+        if !scheme.eq_ignore_ascii_case(PAYMENT_REQUEST_SCHEME) {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidPaymentRequest));
+        }
+
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let recipient = PublicAddress::from_hex(address)?;
+
+        let mut amount = None;
+        let mut asset_id = None;
+        let mut memo = None;
+        let mut expiration = None;
+
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let (name, value) = parameter
+                .split_once('=')
+                .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidPaymentRequest))?;
+
+            let duplicate = match name {
+                "amount" => amount.replace(parse_number(value)?).is_some(),
+                "asset" => asset_id.replace(parse_asset_id(value)?).is_some(),
+                "memo" => memo.replace(parse_memo(value)?).is_some(),
+                "expiration" => expiration.replace(parse_number(value)?).is_some(),
+                _ if name.starts_with("req-") => {
+                    return Err(IronfishError::new(IronfishErrorKind::InvalidPaymentRequest))
+                }
+                _ => false,
+            };
+
+            if duplicate {
+                return Err(IronfishError::new(IronfishErrorKind::InvalidPaymentRequest));
+            }
+        }
+
+        Ok(PaymentRequest {
+            recipient,
+            asset_id: asset_id.unwrap_or(NATIVE_ASSET),
+            amount: amount
+                .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidPaymentRequest))?,
+            memo: memo.unwrap_or_default(),
+            expiration,
+        })
+    }
+}
+
+fn parse_number<T: FromStr<Err = std::num::ParseIntError>>(
+    value: &str,
+) -> Result<T, IronfishError> {
+    // Reject signs, so that each number has a single representation
+    if !value.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(IronfishError::new(IronfishErrorKind::InvalidPaymentRequest));
+    }
+
+    value
+        .parse()
+        .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidPaymentRequest, e))
+}
+
+fn parse_asset_id(value: &str) -> Result<AssetIdentifier, IronfishError> {
+    let bytes = hex_to_bytes(value)
+        .map_err(|_| IronfishError::new(IronfishErrorKind::InvalidAssetIdentifier))?;
+    AssetIdentifier::new(bytes)
+}
+
+fn parse_memo(value: &str) -> Result<Memo, IronfishError> {
+    let bytes = percent_decode(value)?;
+    if bytes.len() > MEMO_SIZE {
+        return Err(IronfishError::new(IronfishErrorKind::InvalidMemo));
+    }
+
+    let mut memo = [0; MEMO_SIZE];
+    memo[..bytes.len()].copy_from_slice(&bytes);
+    Ok(Memo(memo))
+}
+
+/// Percent-encode every byte except the unreserved characters of RFC 3986.
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(*byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn percent_decode(value: &str) -> Result<Vec<u8>, IronfishError> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+
+        let hex = [bytes.next(), bytes.next()];
+        let hex = match hex {
+            [Some(high), Some(low)] => [high, low],
+            _ => return Err(IronfishError::new(IronfishErrorKind::InvalidPaymentRequest)),
+        };
+        let hex = std::str::from_utf8(&hex)
+            .map_err(|_| IronfishError::new(IronfishErrorKind::InvalidPaymentRequest))?;
+        let byte = u8::from_str_radix(hex, 16).map_err(|e| {
+            IronfishError::new_with_source(IronfishErrorKind::InvalidPaymentRequest, e)
+        })?;
+        decoded.push(byte);
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use super::PaymentRequest;
+    use crate::{
+        assets::{asset::Asset, asset_identifier::NATIVE_ASSET},
+        errors::IronfishErrorKind,
+        note::Memo,
+        serializing::bytes_to_hex,
+        SaplingKey,
+    };
+
+    #[test]
+    fn test_payment_request_round_trip() {
+        let recipient = SaplingKey::generate_key();
+        let creator = SaplingKey::generate_key();
+        let asset = Asset::new(creator.public_address(), "Fish", "").unwrap();
+
+        let request = PaymentRequest {
+            recipient: recipient.public_address(),
+            asset_id: *asset.id(),
+            amount: 1234,
+            memo: Memo::from("invoice #42 / 100%"),
+            expiration: Some(500),
+        };
+
+        let uri = request.to_string();
+        assert_eq!(
+            uri,
+            format!(
+                "ironfish:{}?amount=1234&asset={}&memo=invoice%20%2342%20%2F%20100%25&expiration=500",
+                recipient.public_address().hex_public_address(),
+                bytes_to_hex(asset.id().as_bytes())
+            )
+        );
+        assert_eq!(uri.parse::<PaymentRequest>().unwrap(), request);
+
+        let sender = SaplingKey::generate_key();
+        let note = request.to_note(sender.public_address());
+        assert_eq!(note.owner(), recipient.public_address());
+        assert_eq!(note.asset_id(), asset.id());
+        assert_eq!(note.value(), 1234);
+        assert_eq!(note.memo(), request.memo);
+        assert_eq!(note.sender(), sender.public_address());
+    }
+
+    #[test]
+    fn test_payment_request_defaults() {
+        let recipient = SaplingKey::generate_key();
+        let address = recipient.public_address().hex_public_address();
+
+        let request = PaymentRequest::new(recipient.public_address(), 5);
+        assert_eq!(
+            request.to_string(),
+            format!("ironfish:{}?amount=5", address)
+        );
+
+        let parsed: PaymentRequest = format!("IRONFISH:{}?amount=5&label=shop", address)
+            .parse()
+            .unwrap();
+        assert_eq!(parsed, request);
+        assert_eq!(parsed.asset_id, NATIVE_ASSET);
+        assert_eq!(parsed.memo, Memo::default());
+        assert_eq!(parsed.expiration, None);
+    }
+
+    #[test]
+    fn test_payment_request_invalid() {
+        let recipient = SaplingKey::generate_key();
+        let address = recipient.public_address().hex_public_address();
+
+        let invalid = [
+            (
+                format!("bitcoin:{}?amount=5", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=-5", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=18446744073709551616", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=5&amount=6", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=5&req-signature=abc", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=5&memo=%2", address),
+                IronfishErrorKind::InvalidPaymentRequest,
+            ),
+            (
+                format!("ironfish:{}?amount=5&memo={}", address, "a".repeat(33)),
+                IronfishErrorKind::InvalidMemo,
+            ),
+            (
+                format!("ironfish:{}?amount=5&asset=1234", address),
+                IronfishErrorKind::InvalidAssetIdentifier,
+            ),
+            (
+                "ironfish:1234?amount=5".to_string(),
+                IronfishErrorKind::InvalidPublicAddress,
+            ),
+        ];
+
+        for (uri, kind) in invalid {
+            let err = uri.parse::<PaymentRequest>().unwrap_err();
+            assert_eq!(err.kind, kind, "{}", uri);
+        }
+    }
+}
@@ -2,10 +2,22 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::{
+    errors::{IronfishError, IronfishErrorKind},
+    sapling_verifier::{
+        SAPLING_MINT_VERIFYING_KEY_FILE, SAPLING_OUTPUT_VERIFYING_KEY_FILE,
+        SAPLING_SPEND_VERIFYING_KEY_FILE,
+    },
+};
 use blstrs::Bls12;
 use ironfish_bellperson::groth16;
+use std::{fs::File, io, path::Path};
 
-#[cfg(not(doc))]
+const SAPLING_SPEND_PARAMS_FILE: &str = "sapling-spend.params";
+const SAPLING_OUTPUT_PARAMS_FILE: &str = "sapling-output.params";
+const SAPLING_MINT_PARAMS_FILE: &str = "sapling-mint.params";
+
+#[cfg(all(feature = "embedded-params", not(doc)))]
 macro_rules! include_params {
     ( $name:literal ) => {
         include_bytes!(concat!(env!("OUT_DIR"), "/sapling_params/", $name))
@@ -15,15 +27,18 @@ macro_rules! include_params {
 // When building documentation (especially on docs.rs), it's quite possible that the parameter
 // files won't be available, so don't even attempt to include them. This will also speed up
 // documentation builds.
-#[cfg(doc)]
+#[cfg(all(feature = "embedded-params", doc))]
 macro_rules! include_params {
     ( $name:literal ) => {
         b""
     };
 }
 
+#[cfg(feature = "embedded-params")]
 static SAPLING_SPEND_PARAMS: &[u8] = include_params!("sapling-spend.params");
+#[cfg(feature = "embedded-params")]
 static SAPLING_OUTPUT_PARAMS: &[u8] = include_params!("sapling-output.params");
+#[cfg(feature = "embedded-params")]
 static SAPLING_MINT_PARAMS: &[u8] = include_params!("sapling-mint.params");
 
 // The main entry-point to the sapling API. Construct this with loaded parameters, and then call
@@ -46,31 +61,126 @@ pub struct Sapling {
 impl Sapling {
     /// Initialize a Sapling instance and prepare for proving. Load the parameters from files
     /// at a known location (`$OUT_DIR/sapling_params`).
+    #[cfg(feature = "embedded-params")]
     pub fn load() -> Self {
-        let spend_params = Sapling::load_params(SAPLING_SPEND_PARAMS);
-        let output_params = Sapling::load_params(SAPLING_OUTPUT_PARAMS);
-        let mint_params = Sapling::load_params(SAPLING_MINT_PARAMS);
+        Sapling::load_from_reader(
+            SAPLING_SPEND_PARAMS,
+            SAPLING_OUTPUT_PARAMS,
+            SAPLING_MINT_PARAMS,
+        )
+        .expect("embedded sapling parameters should be valid")
+    }
+
+    /// Initialize a Sapling instance from the parameter files in `directory`, named
+    /// `sapling-spend.params`, `sapling-output.params` and `sapling-mint.params`.
+    ///
+    /// The parameters are not checked for validity, so they should only be loaded from a
+    /// trusted source.
+    pub fn load_from_path<P: AsRef<Path>>(directory: P) -> Result<Self, IronfishError> {
+        let directory = directory.as_ref();
+        let open = |name: &str| {
+            File::open(directory.join(name))
+                .map(io::BufReader::new)
+                .map_err(|e| {
+                    IronfishError::new_with_source(IronfishErrorKind::InvalidParameters, e)
+                })
+        };
+
+        Sapling::load_from_reader(
+            open(SAPLING_SPEND_PARAMS_FILE)?,
+            open(SAPLING_OUTPUT_PARAMS_FILE)?,
+            open(SAPLING_MINT_PARAMS_FILE)?,
+        )
+    }
+
+    /// Initialize a Sapling instance from the serialized spend, output and mint parameters.
+    ///
+    /// The parameters are not checked for validity, so they should only be loaded from a
+    /// trusted source.
+    pub fn load_from_reader<S: io::Read, O: io::Read, M: io::Read>(
+        spend_reader: S,
+        output_reader: O,
+        mint_reader: M,
+    ) -> Result<Self, IronfishError> {
+        let spend_params = Sapling::load_params(spend_reader)?;
+        let output_params = Sapling::load_params(output_reader)?;
+        let mint_params = Sapling::load_params(mint_reader)?;
 
         let spend_verifying_key = groth16::prepare_verifying_key(&spend_params.vk);
         let output_verifying_key = groth16::prepare_verifying_key(&output_params.vk);
         let mint_verifying_key = groth16::prepare_verifying_key(&mint_params.vk);
 
-        Sapling {
+        Ok(Sapling {
             spend_verifying_key,
             output_verifying_key,
             mint_verifying_key,
             spend_params,
             output_params,
             mint_params,
+        })
+    }
+
+    /// Write the verifying keys of the parameters to `directory`, so that they can be loaded
+    /// with [`crate::SaplingVerifier::load_from_path`] by nodes that only verify transactions.
+    pub fn write_verifying_keys<P: AsRef<Path>>(&self, directory: P) -> Result<(), IronfishError> {
+        let directory = directory.as_ref();
+
+        for (name, params) in [
+            (SAPLING_SPEND_VERIFYING_KEY_FILE, &self.spend_params),
+            (SAPLING_OUTPUT_VERIFYING_KEY_FILE, &self.output_params),
+            (SAPLING_MINT_VERIFYING_KEY_FILE, &self.mint_params),
+        ] {
+            let mut writer = io::BufWriter::new(File::create(directory.join(name))?);
+            params.vk.write(&mut writer)?;
         }
+
+        Ok(())
     }
 
-    /// Load sapling parameters from a provided filename. The parameters are huge and take a
+    /// Load sapling parameters from a reader. The parameters are huge and take a
     /// couple seconds to load. They primarily contain the "toxic waste" for a specific sapling
     /// curve.
     ///
     /// NOTE: If this is stupidly slow for you, try compiling in --release mode
-    fn load_params(bytes: &[u8]) -> groth16::Parameters<Bls12> {
-        groth16::Parameters::read(bytes, false).unwrap()
+    fn load_params<R: io::Read>(reader: R) -> Result<groth16::Parameters<Bls12>, IronfishError> {
+        groth16::Parameters::read(reader, false)
+            .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidParameters, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Sapling;
+    use crate::errors::IronfishErrorKind;
+    use std::io;
+
+    #[test]
+    fn test_load_from_reader_invalid_params() {
+        let err = match Sapling::load_from_reader(&[0u8; 16][..], io::empty(), io::empty()) {
+            Ok(_) => panic!("truncated parameters should not load"),
+            Err(err) => err,
+        };
+        assert_eq!(err.kind, IronfishErrorKind::InvalidParameters);
+    }
+
+    #[test]
+    fn test_load_from_path_missing_files() {
+        let err = match Sapling::load_from_path("/nonexistent/sapling/params") {
+            Ok(_) => panic!("missing parameters should not load"),
+            Err(err) => err,
+        };
+        assert_eq!(err.kind, IronfishErrorKind::InvalidParameters);
+    }
+
+    #[test]
+    #[cfg(feature = "embedded-params")]
+    fn test_load_from_path() {
+        let directory = concat!(env!("OUT_DIR"), "/sapling_params");
+        let sapling = Sapling::load_from_path(directory).expect("should load parameters");
+        let embedded = Sapling::load();
+
+        assert_eq!(sapling.spend_params.vk, embedded.spend_params.vk);
+        assert_eq!(sapling.output_params.vk, embedded.output_params.vk);
+        assert_eq!(sapling.mint_params.vk, embedded.mint_params.vk);
     }
 }
@@ -2,13 +2,84 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use crate::{
+    errors::{IronfishError, IronfishErrorKind},
+    SaplingVerifier,
+};
+use std::sync::OnceLock;
+
+#[cfg(feature = "transaction-proofs")]
 use crate::Sapling;
-use lazy_static::lazy_static;
+#[cfg(feature = "transaction-proofs")]
+use std::ops::Deref;
 
 pub use blstrs::Scalar;
 
-// Loads the Sapling object once when dereferenced,
-// then reuses the reference on future calls.
-lazy_static! {
-    pub static ref SAPLING: Sapling = Sapling::load();
+#[cfg(feature = "transaction-proofs")]
+static SAPLING_INSTANCE: OnceLock<Sapling> = OnceLock::new();
+
+static SAPLING_VERIFIER_INSTANCE: OnceLock<SaplingVerifier> = OnceLock::new();
+
+/// Handle to the global [`Sapling`] instance.
+///
+/// The instance is loaded once when first dereferenced, then the reference is
+/// reused on future calls. Unless another instance was installed beforehand
+/// with [`init_with`], the parameters embedded in the binary are used.
+#[cfg(feature = "transaction-proofs")]
+pub struct GlobalSapling {
+    _private: (),
+}
+
+#[cfg(feature = "transaction-proofs")]
+pub static SAPLING: GlobalSapling = GlobalSapling { _private: () };
+
+#[cfg(all(feature = "transaction-proofs", feature = "embedded-params"))]
+impl Deref for GlobalSapling {
+    type Target = Sapling;
+
+    fn deref(&self) -> &Sapling {
+        SAPLING_INSTANCE.get_or_init(Sapling::load)
+    }
+}
+
+#[cfg(all(feature = "transaction-proofs", not(feature = "embedded-params")))]
+impl Deref for GlobalSapling {
+    type Target = Sapling;
+
+    fn deref(&self) -> &Sapling {
+        SAPLING_INSTANCE
+            .get()
+            .expect("sapling parameters should be installed with `sapling_bls12::init_with`")
+    }
+}
+
+/// Install `sapling` as the global instance used to create and verify proofs,
+/// for example with parameters loaded via [`Sapling::load_from_path`].
+///
+/// This must be called before [`SAPLING`] is used for the first time, and can
+/// only be called once.
+#[cfg(feature = "transaction-proofs")]
+pub fn init_with(sapling: Sapling) -> Result<(), IronfishError> {
+    SAPLING_INSTANCE
+        .set(sapling)
+        .map_err(|_| IronfishError::new(IronfishErrorKind::SaplingAlreadyInitialized))
+}
+
+/// Install `verifier` as the source of the verifying keys used to verify
+/// transactions, for example with keys loaded via
+/// [`SaplingVerifier::load_from_path`].
+///
+/// When no verifier is installed, the verifying keys of [`SAPLING`] are used.
+/// With the `verifier-only` feature and without `transaction-proofs`, a
+/// verifier must be installed before verifying transactions. This can only be
+/// called once.
+pub fn init_verifier_with(verifier: SaplingVerifier) -> Result<(), IronfishError> {
+    SAPLING_VERIFIER_INSTANCE
+        .set(verifier)
+        .map_err(|_| IronfishError::new(IronfishErrorKind::SaplingAlreadyInitialized))
+}
+
+/// The verifier installed with [`init_verifier_with`], if any.
+pub(crate) fn installed_verifier() -> Option<&'static SaplingVerifier> {
+    SAPLING_VERIFIER_INSTANCE.get()
 }
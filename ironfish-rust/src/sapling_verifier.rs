@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::errors::{IronfishError, IronfishErrorKind};
+use blstrs::Bls12;
+use ironfish_bellperson::groth16;
+use std::{fs::File, io, path::Path};
+
+pub(crate) const SAPLING_SPEND_VERIFYING_KEY_FILE: &str = "sapling-spend.vk";
+pub(crate) const SAPLING_OUTPUT_VERIFYING_KEY_FILE: &str = "sapling-output.vk";
+pub(crate) const SAPLING_MINT_VERIFYING_KEY_FILE: &str = "sapling-mint.vk";
+
+/// The verifying keys of the spend, output and mint circuits, without the
+/// proving parameters.
+///
+/// This is all that is needed to verify transactions, and is a small fraction
+/// of the size of a full [`crate::Sapling`] instance. The keys can be exported
+/// from the full parameters with `Sapling::write_verifying_keys`.
+pub struct SaplingVerifier {
+    pub spend_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
+    pub output_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
+    pub mint_verifying_key: groth16::PreparedVerifyingKey<Bls12>,
+}
+
+impl SaplingVerifier {
+    pub fn new(
+        spend_verifying_key: &groth16::VerifyingKey<Bls12>,
+        output_verifying_key: &groth16::VerifyingKey<Bls12>,
+        mint_verifying_key: &groth16::VerifyingKey<Bls12>,
+    ) -> Self {
+        SaplingVerifier {
+            spend_verifying_key: groth16::prepare_verifying_key(spend_verifying_key),
+            output_verifying_key: groth16::prepare_verifying_key(output_verifying_key),
+            mint_verifying_key: groth16::prepare_verifying_key(mint_verifying_key),
+        }
+    }
+
+    /// Load the verifying keys from the files in `directory`, named
+    /// `sapling-spend.vk`, `sapling-output.vk` and `sapling-mint.vk`.
+    pub fn load_from_path<P: AsRef<Path>>(directory: P) -> Result<Self, IronfishError> {
+        let directory = directory.as_ref();
+        let open = |name: &str| {
+            File::open(directory.join(name))
+                .map(io::BufReader::new)
+                .map_err(|e| {
+                    IronfishError::new_with_source(IronfishErrorKind::InvalidParameters, e)
+                })
+        };
+
+        SaplingVerifier::load_from_reader(
+            open(SAPLING_SPEND_VERIFYING_KEY_FILE)?,
+            open(SAPLING_OUTPUT_VERIFYING_KEY_FILE)?,
+            open(SAPLING_MINT_VERIFYING_KEY_FILE)?,
+        )
+    }
+
+    /// Load the serialized spend, output and mint verifying keys.
+    pub fn load_from_reader<S: io::Read, O: io::Read, M: io::Read>(
+        spend_reader: S,
+        output_reader: O,
+        mint_reader: M,
+    ) -> Result<Self, IronfishError> {
+        Ok(SaplingVerifier::new(
+            &read_verifying_key(spend_reader)?,
+            &read_verifying_key(output_reader)?,
+            &read_verifying_key(mint_reader)?,
+        ))
+    }
+}
+
+fn read_verifying_key<R: io::Read>(
+    reader: R,
+) -> Result<groth16::VerifyingKey<Bls12>, IronfishError> {
+    groth16::VerifyingKey::read(reader)
+        .map_err(|e| IronfishError::new_with_source(IronfishErrorKind::InvalidParameters, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::SaplingVerifier;
+    use crate::errors::IronfishErrorKind;
+    use std::io;
+
+    #[test]
+    fn test_load_from_reader_invalid_keys() {
+        let err = match SaplingVerifier::load_from_reader(&[0u8; 16][..], io::empty(), io::empty())
+        {
+            Ok(_) => panic!("truncated verifying keys should not load"),
+            Err(err) => err,
+        };
+        assert_eq!(err.kind, IronfishErrorKind::InvalidParameters);
+    }
+}
@@ -6,7 +6,14 @@ use std::io;
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
-use crate::{assets::asset_identifier::AssetIdentifier, errors::IronfishError};
+use crate::{
+    assets::{asset::ID_LENGTH, asset_identifier::AssetIdentifier},
+    errors::{ErrorContext, IronfishError},
+};
+
+/// Size of a serialized [`BurnDescription`]: the asset identifier and the
+/// value.
+pub const BURN_DESCRIPTION_SIZE: usize = ID_LENGTH + 8;
 
 /// Parameters used to build a burn description
 pub struct BurnBuilder {
@@ -28,6 +35,18 @@ impl BurnBuilder {
             value: self.value,
         }
     }
+
+    /// Read a [`BurnBuilder`] written with [`BurnBuilder::write`], which uses
+    /// the same format as [`BurnDescription`].
+    pub fn read<R: io::Read>(reader: R) -> Result<Self, IronfishError> {
+        let description = BurnDescription::read(reader)?;
+
+        Ok(Self::new(description.asset_id, description.value))
+    }
+
+    pub fn write<W: io::Write>(&self, writer: W) -> Result<(), IronfishError> {
+        self.build().write(writer)
+    }
 }
 
 /// This description represents an action to decrease the supply of an existing
@@ -58,8 +77,8 @@ impl BurnDescription {
     }
 
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
-        let asset_id = AssetIdentifier::read(&mut reader)?;
-        let value = reader.read_u64::<LittleEndian>()?;
+        let asset_id = AssetIdentifier::read(&mut reader).context("asset_id")?;
+        let value = reader.read_u64::<LittleEndian>().context("value")?;
 
         Ok(BurnDescription { asset_id, value })
     }
@@ -0,0 +1,365 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Selection of the notes to spend in a transaction.
+//!
+//! Given a pool of spendable notes and the value needed for each asset,
+//! [`select_notes`] chooses which notes to spend using a
+//! [`SelectionStrategy`]. [`crate::transaction::ProposedTransaction::select_spends`]
+//! uses it to fund the outputs already added to a transaction.
+
+use crate::{
+    assets::asset_identifier::AssetIdentifier,
+    errors::{IronfishError, IronfishErrorKind},
+    keys::DEFAULT_DIVERSIFIER_INDEX,
+    note::Note,
+    witness::WitnessTrait,
+};
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+/// Maximum number of combinations of notes tried by [`MinimizeChange`].
+const MINIMIZE_CHANGE_MAX_TRIES: usize = 100_000;
+
+/// Number of random selections tried by [`RandomSelection`] before falling
+/// back to [`LargestFirst`].
+const RANDOM_SELECTION_MAX_TRIES: usize = 16;
+
+/// A note that can be spent, along with its witness.
+pub struct SpendableNote<W: WitnessTrait> {
+    pub note: Note,
+    pub witness: W,
+    /// The diversifier index of the address that owns the note, see
+    /// [`crate::ViewKey::diversified_public_address`].
+    pub diversifier_index: u64,
+}
+
+impl<W: WitnessTrait> SpendableNote<W> {
+    /// A note owned by the default address of an account.
+    pub fn new(note: Note, witness: W) -> Self {
+        SpendableNote {
+            note,
+            witness,
+            diversifier_index: DEFAULT_DIVERSIFIER_INDEX,
+        }
+    }
+}
+
+/// A way of choosing which notes to spend.
+pub trait SelectionStrategy {
+    /// Choose notes among `values`, the values of notes of a single asset,
+    /// that add up to at least `target`, using at most `max_spends` notes.
+    ///
+    /// Returns the indexes of the chosen notes in `values`, or `None` if no
+    /// such selection was found.
+    fn select(&self, values: &[u64], target: u64, max_spends: usize) -> Option<Vec<usize>>;
+}
+
+/// Spend the notes with the largest values first. This uses the fewest notes,
+/// which keeps transactions small.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LargestFirst;
+
+impl SelectionStrategy for LargestFirst {
+    fn select(&self, values: &[u64], target: u64, max_spends: usize) -> Option<Vec<usize>> {
+        let mut indexes = (0..values.len()).collect::<Vec<_>>();
+        indexes.sort_by(|a, b| values[*b].cmp(&values[*a]));
+
+        accumulate(values, indexes, target, max_spends)
+    }
+}
+
+/// Spend the combination of notes that leaves the least change, preferring
+/// combinations of fewer notes. This avoids creating many small change notes.
+///
+/// The search is bounded, so the result is the best combination found within
+/// a fixed number of tries, and is never worse than [`LargestFirst`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinimizeChange;
+
+impl SelectionStrategy for MinimizeChange {
+    fn select(&self, values: &[u64], target: u64, max_spends: usize) -> Option<Vec<usize>> {
+        let best = LargestFirst.select(values, target, max_spends)?;
+        let best_total = best.iter().map(|index| values[*index]).sum::<u64>();
+
+        let mut indexes = (0..values.len()).collect::<Vec<_>>();
+        indexes.sort_by(|a, b| values[*b].cmp(&values[*a]));
+
+        // Sum of the values from each position to the end, used to stop
+        // exploring branches that cannot reach the target
+        let mut remaining = vec![0u64; indexes.len() + 1];
+        for position in (0..indexes.len()).rev() {
+            remaining[position] = remaining[position + 1].saturating_add(values[indexes[position]]);
+        }
+
+        let mut search = ChangeSearch {
+            values,
+            indexes: &indexes,
+            remaining: &remaining,
+            target,
+            max_spends,
+            tries: 0,
+            current: vec![],
+            best,
+            best_total,
+        };
+        search.explore(0, 0);
+
+        Some(search.best)
+    }
+}
+
+/// Depth-first search over the notes, sorted by decreasing value, for the
+/// selection with the smallest total that covers the target.
+struct ChangeSearch<'a> {
+    values: &'a [u64],
+    indexes: &'a [usize],
+    remaining: &'a [u64],
+    target: u64,
+    max_spends: usize,
+    tries: usize,
+    current: Vec<usize>,
+    best: Vec<usize>,
+    best_total: u64,
+}
+
+impl ChangeSearch<'_> {
+    fn explore(&mut self, position: usize, total: u64) {
+        self.tries += 1;
+
+        if total >= self.target {
+            if total < self.best_total
+                || (total == self.best_total && self.current.len() < self.best.len())
+            {
+                self.best = self.current.clone();
+                self.best_total = total;
+            }
+            return;
+        }
+
+        if self.tries >= MINIMIZE_CHANGE_MAX_TRIES
+            || self.best_total == self.target
+            || position == self.indexes.len()
+            || self.current.len() == self.max_spends
+            || total.saturating_add(self.remaining[position]) < self.target
+        {
+            return;
+        }
+
+        let index = self.indexes[position];
+        let with_note = total.saturating_add(self.values[index]);
+
+        // Including a note never helps if it already overshoots the best
+        // selection found so far
+        if with_note <= self.best_total {
+            self.current.push(index);
+            self.explore(position + 1, with_note);
+            self.current.pop();
+        }
+
+        self.explore(position + 1, total);
+    }
+}
+
+/// Spend randomly chosen notes, so that the notes spent do not reveal
+/// anything about the amounts being sent.
+///
+/// Falls back to [`LargestFirst`] if no random selection fits in the maximum
+/// number of spends.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RandomSelection;
+
+impl SelectionStrategy for RandomSelection {
+    fn select(&self, values: &[u64], target: u64, max_spends: usize) -> Option<Vec<usize>> {
+        let mut rng = rand::thread_rng();
+        let mut indexes = (0..values.len()).collect::<Vec<_>>();
+
+        for _ in 0..RANDOM_SELECTION_MAX_TRIES {
+            indexes.shuffle(&mut rng);
+            if let Some(selected) = accumulate(values, indexes.clone(), target, max_spends) {
+                return Some(selected);
+            }
+        }
+
+        LargestFirst.select(values, target, max_spends)
+    }
+}
+
+/// Take notes in the given order until their values add up to `target`.
+fn accumulate(
+    values: &[u64],
+    indexes: Vec<usize>,
+    target: u64,
+    max_spends: usize,
+) -> Option<Vec<usize>> {
+    let mut selected = vec![];
+    let mut total = 0u64;
+
+    for index in indexes {
+        if total >= target {
+            break;
+        }
+        if selected.len() == max_spends {
+            return None;
+        }
+
+        total = total.saturating_add(values[index]);
+        selected.push(index);
+    }
+
+    (total >= target).then_some(selected)
+}
+
+/// Choose notes from `pool` to cover the value of each asset in `targets`,
+/// spending at most `max_spends` notes in total.
+///
+/// Returns the indexes of the chosen notes in `pool`, in increasing order.
+pub fn select_notes<W: WitnessTrait>(
+    pool: &[SpendableNote<W>],
+    targets: &HashMap<AssetIdentifier, u64>,
+    max_spends: usize,
+    strategy: &dyn SelectionStrategy,
+) -> Result<Vec<usize>, IronfishError> {
+    let mut selected = vec![];
+
+    // Assets are processed in a fixed order so that the result does not
+    // depend on the iteration order of the map
+    let mut targets = targets.iter().collect::<Vec<_>>();
+    targets.sort_by_key(|(asset_id, _)| asset_id.as_bytes());
+
+    for (asset_id, target) in targets {
+        if *target == 0 {
+            continue;
+        }
+
+        let candidates = pool
+            .iter()
+            .enumerate()
+            .filter(|(_, spendable)| spendable.note.asset_id() == asset_id)
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let values = candidates
+            .iter()
+            .map(|index| pool[*index].note.value())
+            .collect::<Vec<_>>();
+
+        let available = values
+            .iter()
+            .fold(0u64, |total, value| total.saturating_add(*value));
+        if available < *target {
+            return Err(IronfishError::new(IronfishErrorKind::InsufficientFunds));
+        }
+
+        let chosen = strategy
+            .select(&values, *target, max_spends - selected.len())
+            .ok_or_else(|| IronfishError::new(IronfishErrorKind::TooManySpends))?;
+        selected.extend(chosen.into_iter().map(|index| candidates[index]));
+    }
+
+    selected.sort_unstable();
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        select_notes, LargestFirst, MinimizeChange, RandomSelection, SelectionStrategy,
+        SpendableNote,
+    };
+    use crate::{
+        assets::{asset::Asset, asset_identifier::NATIVE_ASSET},
+        errors::IronfishErrorKind,
+        note::Note,
+        test_util::make_fake_witness,
+        SaplingKey,
+    };
+    use std::collections::HashMap;
+
+    fn total(values: &[u64], selected: &[usize]) -> u64 {
+        selected.iter().map(|index| values[*index]).sum()
+    }
+
+    #[test]
+    fn test_largest_first() {
+        let values = [5, 20, 1, 10];
+
+        assert_eq!(LargestFirst.select(&values, 20, 10), Some(vec![1]));
+        assert_eq!(LargestFirst.select(&values, 25, 10), Some(vec![1, 3]));
+        assert_eq!(LargestFirst.select(&values, 31, 10), Some(vec![1, 3, 0]));
+        assert_eq!(LargestFirst.select(&values, 31, 2), None);
+        assert_eq!(LargestFirst.select(&values, 37, 10), None);
+        assert_eq!(LargestFirst.select(&values, 0, 10), Some(vec![]));
+    }
+
+    #[test]
+    fn test_minimize_change() {
+        let values = [50, 30, 21, 9, 7];
+
+        // Exact matches are found, even when the largest notes are not used
+        let selected = MinimizeChange.select(&values, 37, 10).unwrap();
+        assert_eq!(total(&values, &selected), 37);
+
+        // With no exact match, the smallest total over the target is used
+        let selected = MinimizeChange.select(&values, 29, 10).unwrap();
+        assert_eq!(total(&values, &selected), 30);
+        assert_eq!(selected, vec![1]);
+
+        // Fewer notes are preferred for the same total
+        let selected = MinimizeChange.select(&values, 30, 10).unwrap();
+        assert_eq!(selected, vec![1]);
+
+        // The maximum number of spends is respected
+        let selected = MinimizeChange.select(&values, 37, 1).unwrap();
+        assert_eq!(selected, vec![0]);
+        assert_eq!(MinimizeChange.select(&values, 81, 1), None);
+        assert_eq!(MinimizeChange.select(&values, 118, 10), None);
+    }
+
+    #[test]
+    fn test_random_selection() {
+        let values = [5, 20, 1, 10, 3, 8];
+
+        for _ in 0..20 {
+            let selected = RandomSelection.select(&values, 30, 3).unwrap();
+            assert!(selected.len() <= 3);
+            assert!(total(&values, &selected) >= 30);
+        }
+
+        assert_eq!(RandomSelection.select(&values, 48, 10), None);
+        assert_eq!(RandomSelection.select(&values, 40, 2), None);
+    }
+
+    #[test]
+    fn test_select_notes() {
+        let key = SaplingKey::generate_key();
+        let address = key.public_address();
+        let asset = Asset::new(address, "Fish", "").unwrap();
+
+        let pool = [
+            (NATIVE_ASSET, 10),
+            (*asset.id(), 4),
+            (NATIVE_ASSET, 3),
+            (NATIVE_ASSET, 8),
+            (*asset.id(), 7),
+        ]
+        .into_iter()
+        .map(|(asset_id, value)| {
+            let note = Note::new(address, value, "", asset_id, address);
+            let witness = make_fake_witness(&note);
+            SpendableNote::new(note, witness)
+        })
+        .collect::<Vec<_>>();
+
+        let targets = HashMap::from([(NATIVE_ASSET, 15), (*asset.id(), 5)]);
+        let selected = select_notes(&pool, &targets, 10, &LargestFirst).unwrap();
+        assert_eq!(selected, vec![0, 3, 4]);
+
+        let err = select_notes(&pool, &targets, 2, &LargestFirst).unwrap_err();
+        assert_eq!(err.kind, IronfishErrorKind::TooManySpends);
+
+        let targets = HashMap::from([(NATIVE_ASSET, 22), (*asset.id(), 5)]);
+        let err = select_notes(&pool, &targets, 10, &LargestFirst).unwrap_err();
+        assert_eq!(err.kind, IronfishErrorKind::InsufficientFunds);
+    }
+}
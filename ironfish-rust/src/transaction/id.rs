@@ -0,0 +1,110 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use crate::{
+    errors::IronfishError,
+    serializing::{bytes_to_hex, hex_to_bytes},
+};
+use std::{fmt, str::FromStr};
+
+pub const TRANSACTION_ID_SIZE: usize = 32;
+
+/// A 32 byte hash identifying a transaction, as returned by
+/// [`Transaction::hash`](super::Transaction::hash) and
+/// [`Transaction::unsigned_hash`](super::Transaction::unsigned_hash).
+///
+/// Ids are displayed and parsed as lowercase hex, and ordered by their bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TransactionId([u8; TRANSACTION_ID_SIZE]);
+
+impl TransactionId {
+    pub fn new(bytes: [u8; TRANSACTION_ID_SIZE]) -> Self {
+        TransactionId(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; TRANSACTION_ID_SIZE] {
+        &self.0
+    }
+}
+
+impl From<[u8; TRANSACTION_ID_SIZE]> for TransactionId {
+    fn from(bytes: [u8; TRANSACTION_ID_SIZE]) -> Self {
+        TransactionId(bytes)
+    }
+}
+
+impl From<TransactionId> for [u8; TRANSACTION_ID_SIZE] {
+    fn from(id: TransactionId) -> Self {
+        id.0
+    }
+}
+
+impl AsRef<[u8]> for TransactionId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for TransactionId {
+    type Err = IronfishError;
+
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        Ok(TransactionId(hex_to_bytes(hex)?))
+    }
+}
+
+impl fmt::Display for TransactionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", bytes_to_hex(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TransactionId;
+    use crate::errors::IronfishErrorKind;
+
+    #[test]
+    fn test_transaction_id_hex() {
+        let mut bytes = [0; 32];
+        bytes[0] = 0xab;
+        bytes[31] = 0x01;
+        let id = TransactionId::new(bytes);
+
+        let hex = id.to_string();
+        assert_eq!(
+            hex,
+            "ab00000000000000000000000000000000000000000000000000000000000001"
+        );
+        assert_eq!(hex.parse::<TransactionId>().unwrap(), id);
+        assert_eq!(hex.to_uppercase().parse::<TransactionId>().unwrap(), id);
+
+        let short = "ab".parse::<TransactionId>().unwrap_err();
+        assert_eq!(short.kind, IronfishErrorKind::HexError);
+        let invalid = "zz".repeat(32).parse::<TransactionId>().unwrap_err();
+        assert_eq!(invalid.kind, IronfishErrorKind::HexError);
+    }
+
+    #[test]
+    fn test_transaction_id_ordering() {
+        let low = TransactionId::new([0; 32]);
+        let mut bytes = [0; 32];
+        bytes[0] = 1;
+        let high = TransactionId::new(bytes);
+        bytes[31] = 1;
+        let higher = TransactionId::new(bytes);
+
+        let mut ids = vec![higher, low, high];
+        ids.sort();
+        assert_eq!(ids, vec![low, high, higher]);
+
+        // Ordering by bytes matches ordering by hex
+        let mut hexes = vec![higher.to_string(), low.to_string(), high.to_string()];
+        hexes.sort();
+        assert_eq!(
+            hexes,
+            ids.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+    }
+}
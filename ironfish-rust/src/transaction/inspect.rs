@@ -0,0 +1,159 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Decoding of a [`Transaction`] from the point of view of a set of accounts.
+
+use crate::{
+    assets::{asset::Asset, asset_identifier::AssetIdentifier},
+    keys::ViewKeys,
+    note::Note,
+    note_scanner::{NoteMatch, NoteScanner},
+    transaction::Transaction,
+};
+use std::collections::HashMap;
+
+/// A note of one of the accounts that is spent by the transaction.
+#[derive(Clone, Debug)]
+pub struct SpentNote {
+    /// Index of the spend in the transaction.
+    pub spend_index: usize,
+    /// Index of the account, in the keys passed to [`Transaction::inspect`].
+    pub key_index: usize,
+    /// Index of the note in the notes passed to [`Transaction::inspect`].
+    pub note_index: usize,
+    pub note: Note,
+}
+
+/// A change in the supply of an asset, from a mint or a burn.
+#[derive(Clone, Debug)]
+pub struct SupplyChange {
+    pub asset_id: AssetIdentifier,
+    /// The details of the asset. Always known for mints, and known for burns
+    /// of assets that are also minted in the transaction.
+    pub asset: Option<Asset>,
+    pub value: u64,
+}
+
+/// What a transaction does for a set of accounts, returned by
+/// [`Transaction::inspect`].
+#[derive(Clone, Debug)]
+pub struct TransactionSummary {
+    pub fee: i64,
+    pub expiration: u32,
+    /// Notes received by the accounts. The note index is the index of the
+    /// output in the transaction.
+    pub received: Vec<NoteMatch>,
+    /// Notes sent by the accounts, including their change notes. The note
+    /// index is the index of the output in the transaction.
+    pub sent: Vec<NoteMatch>,
+    /// Notes of the accounts spent by the transaction.
+    pub spent: Vec<SpentNote>,
+    pub mints: Vec<SupplyChange>,
+    pub burns: Vec<SupplyChange>,
+}
+
+impl TransactionSummary {
+    /// The net change of the balance of each asset for an account: the value
+    /// of the notes received minus the value of the notes spent.
+    ///
+    /// Assets whose balance does not change are not included.
+    pub fn balance_changes(&self, key_index: usize) -> HashMap<AssetIdentifier, i128> {
+        let mut changes = HashMap::new();
+
+        for received in self.received.iter().filter(|m| m.key_index == key_index) {
+            *changes.entry(*received.note.asset_id()).or_insert(0) +=
+                i128::from(received.note.value());
+        }
+
+        for spent in self.spent.iter().filter(|s| s.key_index == key_index) {
+            *changes.entry(*spent.note.asset_id()).or_insert(0) -= i128::from(spent.note.value());
+        }
+
+        changes.retain(|_, change| *change != 0);
+        changes
+    }
+}
+
+impl Transaction {
+    /// Decode this transaction for the accounts with the given keys.
+    ///
+    /// Outputs are decrypted with the incoming and outgoing view keys of each
+    /// account. Spends are attributed by matching their nullifiers with the
+    /// nullifiers of `notes`, the notes of the accounts along with their
+    /// positions in the note commitment tree.
+    pub fn inspect(&self, keys: &[ViewKeys], notes: &[(Note, u64)]) -> TransactionSummary {
+        let scanner = NoteScanner::new(
+            keys.iter()
+                .map(|key| key.incoming_view_key.clone())
+                .collect(),
+            keys.iter()
+                .map(|key| key.outgoing_view_key.clone())
+                .collect(),
+        );
+        let merkle_notes = self
+            .outputs
+            .iter()
+            .map(|output| output.merkle_note())
+            .collect::<Vec<_>>();
+        let scan = scanner.scan(&merkle_notes);
+
+        let mut nullifiers = HashMap::new();
+        for (key_index, key) in keys.iter().enumerate() {
+            for (note_index, (note, position)) in notes.iter().enumerate() {
+                let nullifier = note.nullifier(&key.view_key, *position);
+                nullifiers.insert(nullifier.0, (key_index, note_index));
+            }
+        }
+
+        let spent = self
+            .spends
+            .iter()
+            .enumerate()
+            .filter_map(|(spend_index, spend)| {
+                nullifiers
+                    .get(&spend.nullifier().0)
+                    .map(|(key_index, note_index)| SpentNote {
+                        spend_index,
+                        key_index: *key_index,
+                        note_index: *note_index,
+                        note: notes[*note_index].0.clone(),
+                    })
+            })
+            .collect();
+
+        let mints = self
+            .mints
+            .iter()
+            .map(|mint| SupplyChange {
+                asset_id: *mint.asset.id(),
+                asset: Some(mint.asset),
+                value: mint.value,
+            })
+            .collect();
+
+        let burns = self
+            .burns
+            .iter()
+            .map(|burn| SupplyChange {
+                asset_id: burn.asset_id,
+                asset: self
+                    .mints
+                    .iter()
+                    .find(|mint| mint.asset.id() == &burn.asset_id)
+                    .map(|mint| mint.asset),
+                value: burn.value,
+            })
+            .collect();
+
+        TransactionSummary {
+            fee: self.fee,
+            expiration: self.expiration,
+            received: scan.received,
+            sent: scan.sent,
+            spent,
+            mints,
+            burns,
+        }
+    }
+}
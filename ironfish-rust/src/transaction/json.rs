@@ -0,0 +1,657 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! JSON representation of transactions, enabled by the `serde` feature.
+//!
+//! Every field maps to a field of the binary format, so a value converts
+//! losslessly between the two. Curve points, scalars, proofs, signatures
+//! and other byte arrays are lowercase hex strings of their binary
+//! serialization. Amounts are decimal strings, because they do not always
+//! fit in the numbers of JSON parsers. Unknown fields are rejected.
+//!
+//! # Schema
+//!
+//! [`Transaction`]:
+//!
+//! ```text
+//! {
+//!   "version": number,                     transaction version
+//!   "fee": string,                         i64 amount, may be negative
+//!   "expiration": number,                  u32 sequence, 0 for none
+//!   "randomizedPublicKey": hex(32),
+//!   "spends": [SpendDescription],
+//!   "outputs": [OutputDescription],
+//!   "mints": [MintDescription],
+//!   "burns": [BurnDescription],
+//!   "bindingSignature": hex(64)
+//! }
+//! ```
+//!
+//! [`SpendDescription`]:
+//!
+//! ```text
+//! {
+//!   "proof": hex(192),
+//!   "valueCommitment": hex(32),
+//!   "rootHash": hex(32),
+//!   "treeSize": number,                    u32
+//!   "nullifier": hex(32),
+//!   "authorizingSignature": hex(64)
+//! }
+//! ```
+//!
+//! [`OutputDescription`]:
+//!
+//! ```text
+//! {
+//!   "proof": hex(192),
+//!   "merkleNote": MerkleNote
+//! }
+//! ```
+//!
+//! [`MerkleNote`]:
+//!
+//! ```text
+//! {
+//!   "valueCommitment": hex(32),
+//!   "noteCommitment": hex(32),
+//!   "ephemeralPublicKey": hex(32),
+//!   "encryptedNote": hex(104),
+//!   "noteEncryptionKeys": hex(80)
+//! }
+//! ```
+//!
+//! [`MintDescription`]:
+//!
+//! ```text
+//! {
+//!   "proof": hex(192),
+//!   "asset": Asset,
+//!   "value": string,                       u64 amount
+//!   "owner": hex(32),                      public address
+//!   "transferOwnershipTo": hex(32) | null, public address
+//!   "authorizingSignature": hex(64)
+//! }
+//! ```
+//!
+//! In transactions of versions without ownership transfers, `owner` must be
+//! the creator of the asset and `transferOwnershipTo` must be `null`.
+//!
+//! [`BurnDescription`]:
+//!
+//! ```text
+//! {
+//!   "assetId": hex(32),
+//!   "value": string                        u64 amount
+//! }
+//! ```
+//!
+//! [`Asset`]:
+//!
+//! ```text
+//! {
+//!   "id": hex(32),                         must match the other fields
+//!   "creator": hex(32),                    public address
+//!   "name": hex(32),                       zero padded
+//!   "metadata": hex(96),                   zero padded
+//!   "nonce": number                        u8
+//! }
+//! ```
+//!
+//! [`Note`]:
+//!
+//! ```text
+//! {
+//!   "owner": hex(32),                      public address
+//!   "assetId": hex(32),
+//!   "value": string,                       u64 amount
+//!   "randomness": hex(32),
+//!   "memo": hex(32),
+//!   "sender": hex(32)                      public address
+//! }
+//! ```
+
+use crate::{
+    assets::{asset::Asset, asset_identifier::AssetIdentifier},
+    errors::{IronfishError, IronfishErrorKind},
+    merkle_note::MerkleNote,
+    note::{Memo, Note},
+    serializing::{bytes_to_hex, hex_to_bytes, hex_to_vec_bytes, read_point, read_scalar},
+    transaction::{
+        burns::BurnDescription, mints::MintDescription, outputs::OutputDescription,
+        spends::SpendDescription, Transaction, TransactionVersion,
+    },
+    PublicAddress,
+};
+use blstrs::Bls12;
+use ff::PrimeField;
+use group::GroupEncoding;
+use ironfish_bellperson::groth16;
+use ironfish_zkp::{
+    redjubjub::{self, Signature},
+    Nullifier,
+};
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Implement [`Serialize`] and [`Deserialize`] for a type through its JSON
+/// representation, converting errors to serde errors.
+macro_rules! impl_serde {
+    ($type:ty, $json:ty) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                <$json>::try_from(self)
+                    .map_err(ser::Error::custom)?
+                    .serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <$json>::deserialize(deserializer)?
+                    .try_into()
+                    .map_err(de::Error::custom)
+            }
+        }
+    };
+}
+
+impl_serde!(Transaction, TransactionJson);
+impl_serde!(SpendDescription, SpendDescriptionJson);
+impl_serde!(OutputDescription, OutputDescriptionJson);
+impl_serde!(MerkleNote, MerkleNoteJson);
+impl_serde!(MintDescription, MintDescriptionJson);
+impl_serde!(BurnDescription, BurnDescriptionJson);
+impl_serde!(Asset, AssetJson);
+impl_serde!(Note, NoteJson);
+
+/// Decode a hex string and read a value from the decoded bytes, which must
+/// all be consumed.
+fn read_hex<T>(
+    hex: &str,
+    read: impl FnOnce(&mut &[u8]) -> Result<T, IronfishError>,
+) -> Result<T, IronfishError> {
+    let bytes = hex_to_vec_bytes(hex)?;
+    let mut reader = &bytes[..];
+    let value = read(&mut reader)?;
+
+    if !reader.is_empty() {
+        return Err(IronfishError::new(IronfishErrorKind::InvalidData));
+    }
+
+    Ok(value)
+}
+
+fn proof_to_hex(proof: &groth16::Proof<Bls12>) -> Result<String, IronfishError> {
+    let mut bytes = vec![];
+    proof.write(&mut bytes)?;
+
+    Ok(bytes_to_hex(&bytes))
+}
+
+fn proof_from_hex(hex: &str) -> Result<groth16::Proof<Bls12>, IronfishError> {
+    read_hex(hex, |reader| Ok(groth16::Proof::read(reader)?))
+}
+
+fn signature_to_hex(signature: &Signature) -> Result<String, IronfishError> {
+    let mut bytes = vec![];
+    signature.write(&mut bytes)?;
+
+    Ok(bytes_to_hex(&bytes))
+}
+
+fn signature_from_hex(hex: &str) -> Result<Signature, IronfishError> {
+    read_hex(hex, |reader| Ok(Signature::read(reader)?))
+}
+
+fn address_from_hex(hex: &str) -> Result<PublicAddress, IronfishError> {
+    PublicAddress::new(&hex_to_bytes(hex)?)
+}
+
+fn asset_id_from_hex(hex: &str) -> Result<AssetIdentifier, IronfishError> {
+    AssetIdentifier::new(hex_to_bytes(hex)?)
+}
+
+fn amount_from_str<T: std::str::FromStr>(amount: &str) -> Result<T, IronfishError> {
+    amount
+        .parse()
+        .map_err(|_| IronfishError::new(IronfishErrorKind::InvalidData))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct TransactionJson {
+    version: u8,
+    fee: String,
+    expiration: u32,
+    randomized_public_key: String,
+    spends: Vec<SpendDescriptionJson>,
+    outputs: Vec<OutputDescriptionJson>,
+    mints: Vec<MintDescriptionJson>,
+    burns: Vec<BurnDescriptionJson>,
+    binding_signature: String,
+}
+
+impl TryFrom<&Transaction> for TransactionJson {
+    type Error = IronfishError;
+
+    fn try_from(transaction: &Transaction) -> Result<Self, Self::Error> {
+        Ok(TransactionJson {
+            version: transaction.version.as_u8(),
+            fee: transaction.fee.to_string(),
+            expiration: transaction.expiration,
+            randomized_public_key: bytes_to_hex(&transaction.randomized_public_key.0.to_bytes()),
+            spends: transaction
+                .spends
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            outputs: transaction
+                .outputs
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            mints: transaction
+                .mints
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            burns: transaction
+                .burns
+                .iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            binding_signature: signature_to_hex(&transaction.binding_signature)?,
+        })
+    }
+}
+
+impl TryFrom<TransactionJson> for Transaction {
+    type Error = IronfishError;
+
+    fn try_from(json: TransactionJson) -> Result<Self, Self::Error> {
+        let version = TransactionVersion::try_from(json.version)?;
+
+        let mints = json
+            .mints
+            .into_iter()
+            .map(MintDescription::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        if !version.has_mint_transfer_ownership_to()
+            && mints.iter().any(|mint| {
+                mint.transfer_ownership_to.is_some() || mint.owner != mint.asset.creator
+            })
+        {
+            return Err(IronfishError::new(
+                IronfishErrorKind::InvalidTransactionVersion,
+            ));
+        }
+
+        Ok(Transaction {
+            version,
+            fee: amount_from_str(&json.fee)?,
+            spends: json
+                .spends
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            outputs: json
+                .outputs
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            mints,
+            burns: json
+                .burns
+                .into_iter()
+                .map(TryFrom::try_from)
+                .collect::<Result<_, _>>()?,
+            binding_signature: signature_from_hex(&json.binding_signature)?,
+            expiration: json.expiration,
+            randomized_public_key: read_hex(&json.randomized_public_key, |reader| {
+                Ok(redjubjub::PublicKey::read(reader)?)
+            })?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SpendDescriptionJson {
+    proof: String,
+    value_commitment: String,
+    root_hash: String,
+    tree_size: u32,
+    nullifier: String,
+    authorizing_signature: String,
+}
+
+impl TryFrom<&SpendDescription> for SpendDescriptionJson {
+    type Error = IronfishError;
+
+    fn try_from(spend: &SpendDescription) -> Result<Self, Self::Error> {
+        Ok(SpendDescriptionJson {
+            proof: proof_to_hex(&spend.proof)?,
+            value_commitment: bytes_to_hex(&spend.value_commitment.to_bytes()),
+            root_hash: bytes_to_hex(&spend.root_hash.to_repr()),
+            tree_size: spend.tree_size,
+            nullifier: bytes_to_hex(&spend.nullifier.0),
+            authorizing_signature: signature_to_hex(&spend.authorizing_signature)?,
+        })
+    }
+}
+
+impl TryFrom<SpendDescriptionJson> for SpendDescription {
+    type Error = IronfishError;
+
+    fn try_from(json: SpendDescriptionJson) -> Result<Self, Self::Error> {
+        Ok(SpendDescription {
+            proof: proof_from_hex(&json.proof)?,
+            value_commitment: read_hex(&json.value_commitment, |reader| read_point(reader))?,
+            root_hash: read_hex(&json.root_hash, |reader| read_scalar(reader))?,
+            tree_size: json.tree_size,
+            nullifier: Nullifier(hex_to_bytes(&json.nullifier)?),
+            authorizing_signature: signature_from_hex(&json.authorizing_signature)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct OutputDescriptionJson {
+    proof: String,
+    merkle_note: MerkleNoteJson,
+}
+
+impl TryFrom<&OutputDescription> for OutputDescriptionJson {
+    type Error = IronfishError;
+
+    fn try_from(output: &OutputDescription) -> Result<Self, Self::Error> {
+        Ok(OutputDescriptionJson {
+            proof: proof_to_hex(&output.proof)?,
+            merkle_note: MerkleNoteJson::try_from(&output.merkle_note)?,
+        })
+    }
+}
+
+impl TryFrom<OutputDescriptionJson> for OutputDescription {
+    type Error = IronfishError;
+
+    fn try_from(json: OutputDescriptionJson) -> Result<Self, Self::Error> {
+        Ok(OutputDescription {
+            proof: proof_from_hex(&json.proof)?,
+            merkle_note: json.merkle_note.try_into()?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MerkleNoteJson {
+    value_commitment: String,
+    note_commitment: String,
+    ephemeral_public_key: String,
+    encrypted_note: String,
+    note_encryption_keys: String,
+}
+
+impl TryFrom<&MerkleNote> for MerkleNoteJson {
+    type Error = IronfishError;
+
+    fn try_from(merkle_note: &MerkleNote) -> Result<Self, Self::Error> {
+        Ok(MerkleNoteJson {
+            value_commitment: bytes_to_hex(&merkle_note.value_commitment.to_bytes()),
+            note_commitment: bytes_to_hex(&merkle_note.note_commitment.to_repr()),
+            ephemeral_public_key: bytes_to_hex(&merkle_note.ephemeral_public_key.to_bytes()),
+            encrypted_note: bytes_to_hex(&merkle_note.encrypted_note),
+            note_encryption_keys: bytes_to_hex(&merkle_note.note_encryption_keys),
+        })
+    }
+}
+
+impl TryFrom<MerkleNoteJson> for MerkleNote {
+    type Error = IronfishError;
+
+    fn try_from(json: MerkleNoteJson) -> Result<Self, Self::Error> {
+        Ok(MerkleNote {
+            value_commitment: read_hex(&json.value_commitment, |reader| read_point(reader))?,
+            note_commitment: read_hex(&json.note_commitment, |reader| read_scalar(reader))?,
+            ephemeral_public_key: read_hex(&json.ephemeral_public_key, |reader| {
+                read_point(reader)
+            })?,
+            encrypted_note: hex_to_bytes(&json.encrypted_note)?,
+            note_encryption_keys: hex_to_bytes(&json.note_encryption_keys)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct MintDescriptionJson {
+    proof: String,
+    asset: AssetJson,
+    value: String,
+    owner: String,
+    transfer_ownership_to: Option<String>,
+    authorizing_signature: String,
+}
+
+impl TryFrom<&MintDescription> for MintDescriptionJson {
+    type Error = IronfishError;
+
+    fn try_from(mint: &MintDescription) -> Result<Self, Self::Error> {
+        Ok(MintDescriptionJson {
+            proof: proof_to_hex(&mint.proof)?,
+            asset: AssetJson::try_from(&mint.asset)?,
+            value: mint.value.to_string(),
+            owner: mint.owner.hex_public_address(),
+            transfer_ownership_to: mint
+                .transfer_ownership_to
+                .map(|address| address.hex_public_address()),
+            authorizing_signature: signature_to_hex(&mint.authorizing_signature)?,
+        })
+    }
+}
+
+impl TryFrom<MintDescriptionJson> for MintDescription {
+    type Error = IronfishError;
+
+    fn try_from(json: MintDescriptionJson) -> Result<Self, Self::Error> {
+        Ok(MintDescription {
+            proof: proof_from_hex(&json.proof)?,
+            asset: json.asset.try_into()?,
+            value: amount_from_str(&json.value)?,
+            owner: address_from_hex(&json.owner)?,
+            transfer_ownership_to: json
+                .transfer_ownership_to
+                .as_deref()
+                .map(address_from_hex)
+                .transpose()?,
+            authorizing_signature: signature_from_hex(&json.authorizing_signature)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct BurnDescriptionJson {
+    asset_id: String,
+    value: String,
+}
+
+impl TryFrom<&BurnDescription> for BurnDescriptionJson {
+    type Error = IronfishError;
+
+    fn try_from(burn: &BurnDescription) -> Result<Self, Self::Error> {
+        Ok(BurnDescriptionJson {
+            asset_id: bytes_to_hex(burn.asset_id.as_bytes()),
+            value: burn.value.to_string(),
+        })
+    }
+}
+
+impl TryFrom<BurnDescriptionJson> for BurnDescription {
+    type Error = IronfishError;
+
+    fn try_from(json: BurnDescriptionJson) -> Result<Self, Self::Error> {
+        Ok(BurnDescription {
+            asset_id: asset_id_from_hex(&json.asset_id)?,
+            value: amount_from_str(&json.value)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct AssetJson {
+    id: String,
+    creator: String,
+    name: String,
+    metadata: String,
+    nonce: u8,
+}
+
+impl TryFrom<&Asset> for AssetJson {
+    type Error = IronfishError;
+
+    fn try_from(asset: &Asset) -> Result<Self, Self::Error> {
+        Ok(AssetJson {
+            id: bytes_to_hex(asset.id.as_bytes()),
+            creator: asset.creator.hex_public_address(),
+            name: bytes_to_hex(&asset.name),
+            metadata: bytes_to_hex(&asset.metadata),
+            nonce: asset.nonce,
+        })
+    }
+}
+
+impl TryFrom<AssetJson> for Asset {
+    type Error = IronfishError;
+
+    fn try_from(json: AssetJson) -> Result<Self, Self::Error> {
+        let asset = Asset::new_with_nonce(
+            address_from_hex(&json.creator)?,
+            hex_to_bytes(&json.name)?,
+            hex_to_bytes(&json.metadata)?,
+            json.nonce,
+        )?;
+
+        if asset.id != asset_id_from_hex(&json.id)? {
+            return Err(IronfishError::new(
+                IronfishErrorKind::InvalidAssetIdentifier,
+            ));
+        }
+
+        Ok(asset)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct NoteJson {
+    owner: String,
+    asset_id: String,
+    value: String,
+    randomness: String,
+    memo: String,
+    sender: String,
+}
+
+impl TryFrom<&Note> for NoteJson {
+    type Error = IronfishError;
+
+    fn try_from(note: &Note) -> Result<Self, Self::Error> {
+        Ok(NoteJson {
+            owner: note.owner.hex_public_address(),
+            asset_id: bytes_to_hex(note.asset_id.as_bytes()),
+            value: note.value.to_string(),
+            randomness: bytes_to_hex(&note.randomness.to_bytes()),
+            memo: bytes_to_hex(&note.memo.0),
+            sender: note.sender.hex_public_address(),
+        })
+    }
+}
+
+impl TryFrom<NoteJson> for Note {
+    type Error = IronfishError;
+
+    fn try_from(json: NoteJson) -> Result<Self, Self::Error> {
+        Ok(Note {
+            owner: address_from_hex(&json.owner)?,
+            asset_id: asset_id_from_hex(&json.asset_id)?,
+            value: amount_from_str(&json.value)?,
+            randomness: read_hex(&json.randomness, |reader| read_scalar(reader))?,
+            memo: Memo(hex_to_bytes(&json.memo)?),
+            sender: address_from_hex(&json.sender)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        assets::{asset::Asset, asset_identifier::NATIVE_ASSET},
+        note::Note,
+        transaction::burns::BurnDescription,
+        SaplingKey,
+    };
+
+    #[test]
+    fn test_note_json_round_trip() {
+        let owner = SaplingKey::generate_key().public_address();
+        let sender = SaplingKey::generate_key().public_address();
+        let note = Note::new(owner, 42, "memo", NATIVE_ASSET, sender);
+
+        let json = serde_json::to_value(&note).unwrap();
+        assert_eq!(json["owner"], owner.hex_public_address());
+        assert_eq!(json["value"], "42");
+        assert_eq!(json["sender"], sender.hex_public_address());
+
+        let deserialized: Note = serde_json::from_value(json).unwrap();
+        let mut expected = vec![];
+        note.write(&mut expected).unwrap();
+        let mut actual = vec![];
+        deserialized.write(&mut actual).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_asset_json_round_trip() {
+        let creator = SaplingKey::generate_key().public_address();
+        let asset = Asset::new(creator, "Testcoin", "metadata").unwrap();
+
+        let json = serde_json::to_string(&asset).unwrap();
+        let deserialized: Asset = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, asset);
+
+        // The identifier must match the other fields of the asset
+        let mut json = serde_json::to_value(asset).unwrap();
+        json["nonce"] = (asset.nonce().wrapping_add(1)).into();
+        assert!(serde_json::from_value::<Asset>(json).is_err());
+    }
+
+    #[test]
+    fn test_burn_description_json_round_trip() {
+        let burn = BurnDescription {
+            asset_id: NATIVE_ASSET,
+            value: u64::MAX,
+        };
+
+        let json = serde_json::to_string(&burn).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"assetId":"{}","value":"18446744073709551615"}}"#,
+                crate::serializing::bytes_to_hex(NATIVE_ASSET.as_bytes())
+            )
+        );
+
+        let deserialized: BurnDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.asset_id, burn.asset_id);
+        assert_eq!(deserialized.value, burn.value);
+
+        // Unknown fields are rejected
+        let json = json.replace('}', r#","extra":1}"#);
+        assert!(serde_json::from_str::<BurnDescription>(&json).is_err());
+    }
+}
@@ -3,10 +3,11 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    assets::asset::Asset,
-    errors::{IronfishError, IronfishErrorKind},
+    assets::asset::{Asset, ASSET_LENGTH},
+    errors::{ErrorContext, IronfishError, IronfishErrorKind},
+    keys::PUBLIC_ADDRESS_SIZE,
     serializing::read_scalar,
-    transaction::TransactionVersion,
+    transaction::{outputs::PROOF_SIZE, TransactionVersion, TRANSACTION_SIGNATURE_SIZE},
     PublicAddress, SaplingKey,
 };
 use blstrs::{Bls12, Scalar};
@@ -57,6 +58,36 @@ impl MintBuilder {
         self
     }
 
+    /// Read a [`MintBuilder`] written with [`MintBuilder::write`].
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let asset = Asset::read(&mut reader)?;
+        let value = reader.read_u64::<LittleEndian>()?;
+        let transfer_ownership_to = match reader.read_u8()? {
+            0 => None,
+            1 => Some(PublicAddress::read(&mut reader)?),
+            _ => return Err(IronfishError::new(IronfishErrorKind::InvalidData)),
+        };
+
+        Ok(Self {
+            asset,
+            value,
+            transfer_ownership_to,
+        })
+    }
+
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.asset.write(&mut writer)?;
+        writer.write_u64::<LittleEndian>(self.value)?;
+        if let Some(ref transfer_ownership_to) = self.transfer_ownership_to {
+            writer.write_u8(1)?;
+            transfer_ownership_to.write(&mut writer)?;
+        } else {
+            writer.write_u8(0)?;
+        }
+
+        Ok(())
+    }
+
     pub fn build(
         &self,
         proof_generation_key: &ProofGenerationKey,
@@ -183,6 +214,27 @@ impl UnsignedMintDescription {
     }
 }
 
+/// Size of a serialized [`MintDescription`] in a transaction of the given
+/// version, depending on whether the mint transfers the ownership of the asset.
+pub fn mint_description_size(version: TransactionVersion, transfers_ownership: bool) -> usize {
+    //   192 proof
+    // + 161 asset
+    // + 8   value
+    // + 64  authorizing signature
+    let mut size = PROOF_SIZE as usize + ASSET_LENGTH + 8 + TRANSACTION_SIGNATURE_SIZE;
+
+    if version.has_mint_transfer_ownership_to() {
+        // owner and the flag for the new owner
+        size += PUBLIC_ADDRESS_SIZE + 1;
+
+        if transfers_ownership {
+            size += PUBLIC_ADDRESS_SIZE;
+        }
+    }
+
+    size
+}
+
 /// This description represents an action to increase the supply of an existing
 /// asset on Iron Fish
 #[derive(Clone, Debug)]
@@ -318,16 +370,16 @@ impl MintDescription {
         mut reader: R,
         version: TransactionVersion,
     ) -> Result<Self, IronfishError> {
-        let proof = groth16::Proof::read(&mut reader)?;
-        let asset = Asset::read(&mut reader)?;
-        let value = reader.read_u64::<LittleEndian>()?;
+        let proof = groth16::Proof::read(&mut reader).context("proof")?;
+        let asset = Asset::read(&mut reader).context("asset")?;
+        let value = reader.read_u64::<LittleEndian>().context("value")?;
 
         let owner: PublicAddress;
         let transfer_ownership_to;
         if version.has_mint_transfer_ownership_to() {
-            owner = PublicAddress::read(&mut reader)?;
-            transfer_ownership_to = if reader.read_u8()? != 0 {
-                Some(PublicAddress::read(&mut reader)?)
+            owner = PublicAddress::read(&mut reader).context("owner")?;
+            transfer_ownership_to = if reader.read_u8().context("transfer_ownership_to")? != 0 {
+                Some(PublicAddress::read(&mut reader).context("transfer_ownership_to")?)
             } else {
                 None
             }
@@ -336,7 +388,8 @@ impl MintDescription {
             transfer_ownership_to = None;
         }
 
-        let authorizing_signature = redjubjub::Signature::read(&mut reader)?;
+        let authorizing_signature =
+            redjubjub::Signature::read(&mut reader).context("authorizing_signature")?;
 
         Ok(MintDescription {
             proof,
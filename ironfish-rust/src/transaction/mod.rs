@@ -3,7 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    errors::IronfishError,
+    errors::{ErrorContext, IronfishError},
     transaction::{burns::BurnDescription, mints::MintDescription},
     OutputDescription, SpendDescription,
 };
@@ -16,41 +16,53 @@ use std::{
     slice::Iter,
 };
 
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 use crate::errors::IronfishErrorKind;
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 use ironfish_jubjub::ExtendedPoint;
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 use ironfish_zkp::constants::{
     NATIVE_VALUE_COMMITMENT_GENERATOR, VALUE_COMMITMENT_RANDOMNESS_GENERATOR,
 };
 
 pub mod burns;
+pub mod coin_selection;
+pub mod id;
+pub mod inspect;
 pub mod mints;
 pub mod outputs;
+pub mod partially_signed;
 pub mod spends;
 pub mod unsigned;
+pub mod view;
 
 mod version;
 
+#[cfg(feature = "serde")]
+pub mod json;
 #[cfg(feature = "transaction-proofs")]
 mod proposed;
 #[cfg(feature = "transaction-proofs")]
 mod value_balances;
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 mod verify;
 
 #[cfg(test)]
 mod tests;
 
+pub use id::TransactionId;
 pub use version::TransactionVersion;
 
 #[cfg(feature = "transaction-proofs")]
 pub use proposed::ProposedTransaction;
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 pub use verify::batch_verify_transactions;
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
+pub use verify::batch_verify_transactions_with_failures;
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 pub use verify::verify_transaction;
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
+pub use verify::TransactionVerificationFailure;
 
 const SIGNATURE_HASH_PERSONALIZATION: &[u8; 8] = b"IFsighsh";
 const TRANSACTION_SIGNATURE_VERSION: &[u8; 1] = &[0];
@@ -59,6 +71,22 @@ pub const TRANSACTION_PUBLIC_KEY_SIZE: usize = 32;
 pub const TRANSACTION_EXPIRATION_SIZE: usize = 4;
 pub const TRANSACTION_FEE_SIZE: usize = 8;
 
+/// Size of the fields of a serialized [`Transaction`] that do not depend on
+/// its descriptions.
+//   1  version
+// + 32 number of spends, outputs, mints and burns
+// + 8  fee
+// + 4  expiration
+// + 32 randomized public key
+// + 64 binding signature
+// = 141
+pub const TRANSACTION_FIXED_SIZE: usize = 1
+    + 4 * 8
+    + TRANSACTION_FEE_SIZE
+    + TRANSACTION_EXPIRATION_SIZE
+    + TRANSACTION_PUBLIC_KEY_SIZE
+    + TRANSACTION_SIGNATURE_SIZE;
+
 /// A transaction that has been published and can be read by anyone, not storing
 /// any of the working data or private keys used in creating the proofs.
 ///
@@ -107,36 +135,48 @@ impl Transaction {
     /// This is the main entry-point when reconstructing a serialized transaction
     /// for verifying.
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
-        let version = TransactionVersion::read(&mut reader)?;
-        let num_spends = reader.read_u64::<LittleEndian>()?;
-        let num_outputs = reader.read_u64::<LittleEndian>()?;
-        let num_mints = reader.read_u64::<LittleEndian>()?;
-        let num_burns = reader.read_u64::<LittleEndian>()?;
-        let fee = reader.read_i64::<LittleEndian>()?;
-        let expiration = reader.read_u32::<LittleEndian>()?;
-        let randomized_public_key = redjubjub::PublicKey::read(&mut reader)?;
+        let version = TransactionVersion::read(&mut reader).context("version")?;
+        let num_spends = reader.read_u64::<LittleEndian>().context("num_spends")?;
+        let num_outputs = reader.read_u64::<LittleEndian>().context("num_outputs")?;
+        let num_mints = reader.read_u64::<LittleEndian>().context("num_mints")?;
+        let num_burns = reader.read_u64::<LittleEndian>().context("num_burns")?;
+        let fee = reader.read_i64::<LittleEndian>().context("fee")?;
+        let expiration = reader.read_u32::<LittleEndian>().context("expiration")?;
+        let randomized_public_key =
+            redjubjub::PublicKey::read(&mut reader).context("randomized_public_key")?;
 
         let mut spends = Vec::with_capacity(num_spends as usize);
-        for _ in 0..num_spends {
-            spends.push(SpendDescription::read(&mut reader)?);
+        for index in 0..num_spends {
+            spends.push(
+                SpendDescription::read(&mut reader)
+                    .with_context(|| format!("spends[{}]", index))?,
+            );
         }
 
         let mut outputs = Vec::with_capacity(num_outputs as usize);
-        for _ in 0..num_outputs {
-            outputs.push(OutputDescription::read(&mut reader)?);
+        for index in 0..num_outputs {
+            outputs.push(
+                OutputDescription::read(&mut reader)
+                    .with_context(|| format!("outputs[{}]", index))?,
+            );
         }
 
         let mut mints = Vec::with_capacity(num_mints as usize);
-        for _ in 0..num_mints {
-            mints.push(MintDescription::read(&mut reader, version)?);
+        for index in 0..num_mints {
+            mints.push(
+                MintDescription::read(&mut reader, version)
+                    .with_context(|| format!("mints[{}]", index))?,
+            );
         }
 
         let mut burns = Vec::with_capacity(num_burns as usize);
-        for _ in 0..num_burns {
-            burns.push(BurnDescription::read(&mut reader)?);
+        for index in 0..num_burns {
+            burns.push(
+                BurnDescription::read(&mut reader).with_context(|| format!("burns[{}]", index))?,
+            );
         }
 
-        let binding_signature = Signature::read(&mut reader)?;
+        let binding_signature = Signature::read(&mut reader).context("binding_signature")?;
 
         Ok(Transaction {
             version,
@@ -233,6 +273,23 @@ impl Transaction {
         &self.randomized_public_key
     }
 
+    /// The id of the transaction: the blake3 hash of its serialized bytes,
+    /// including the proofs and signatures.
+    pub fn hash(&self) -> Result<TransactionId, IronfishError> {
+        let mut hasher = blake3::Hasher::new();
+        self.write(&mut hasher)?;
+
+        Ok(TransactionId::from(*hasher.finalize().as_bytes()))
+    }
+
+    /// The id of the transaction without its signatures, which is the hash
+    /// returned by [`Transaction::transaction_signature_hash`]. It is the
+    /// same as the [`UnsignedTransaction::hash`](unsigned::UnsignedTransaction::hash)
+    /// of the transaction before it was signed.
+    pub fn unsigned_hash(&self) -> Result<TransactionId, IronfishError> {
+        self.transaction_signature_hash().map(TransactionId::from)
+    }
+
     /// Calculate a hash of the transaction data. This hash was signed by the
     /// private keys when the transaction was constructed, and will now be
     /// reconstructed to verify the signature.
@@ -270,7 +327,7 @@ impl Transaction {
 
     /// Confirm that this transaction was signed by the values it contains.
     /// Called from the public verify function.
-    #[cfg(feature = "transaction-proofs")]
+    #[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
     fn verify_binding_signature(
         &self,
         binding_verification_key: &ExtendedPoint,
@@ -296,7 +353,7 @@ impl Transaction {
 
 /// Convert the integer value to a point on the Jubjub curve, accounting for
 /// negative values
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 fn fee_to_point(value: i64) -> Result<ExtendedPoint, IronfishError> {
     // Can only construct edwards point on positive numbers, so need to
     // add and possibly negate later
@@ -319,7 +376,7 @@ fn fee_to_point(value: i64) -> Result<ExtendedPoint, IronfishError> {
 ///
 /// Does not confirm that the transactions add up to zero. The calculation
 /// for fees and change happens elsewhere.
-#[cfg(feature = "transaction-proofs")]
+#[cfg(any(feature = "transaction-proofs", feature = "verifier-only"))]
 fn calculate_value_balance(
     binding_verification_key: &ExtendedPoint,
     fee: i64,
@@ -3,8 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    errors::{IronfishError, IronfishErrorKind},
-    merkle_note::MerkleNote,
+    errors::{ErrorContext, IronfishError, IronfishErrorKind},
+    merkle_note::{MerkleNote, MERKLE_NOTE_SIZE},
 };
 use blstrs::{Bls12, Scalar};
 use ff::Field;
@@ -17,8 +17,12 @@ use std::io;
 #[cfg(feature = "transaction-proofs")]
 use super::verify::verify_output_proof;
 #[cfg(feature = "transaction-proofs")]
+use crate::serializing::read_scalar;
+#[cfg(feature = "transaction-proofs")]
 use crate::{keys::EphemeralKeyPair, note::Note, sapling_bls12::SAPLING, OutgoingViewKey};
 #[cfg(feature = "transaction-proofs")]
+use byteorder::{ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "transaction-proofs")]
 use ironfish_zkp::{primitives::ValueCommitment, proofs::Output, ProofGenerationKey};
 #[cfg(feature = "transaction-proofs")]
 use rand::thread_rng;
@@ -43,6 +47,9 @@ pub struct OutputBuilder {
 
 pub const PROOF_SIZE: u32 = 192;
 
+/// Size of a serialized [`OutputDescription`].
+pub const OUTPUT_DESCRIPTION_SIZE: usize = PROOF_SIZE as usize + MERKLE_NOTE_SIZE;
+
 #[cfg(feature = "transaction-proofs")]
 impl OutputBuilder {
     /// Create a new [`OutputBuilder`] attempting to create a note.
@@ -74,6 +81,37 @@ impl OutputBuilder {
         ExtendedPoint::from(self.value_commitment.commitment())
     }
 
+    /// Read an [`OutputBuilder`] written with [`OutputBuilder::write`].
+    pub(crate) fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let note = Note::read(&mut reader)?;
+        let value_commitment = ValueCommitment {
+            value: note.value,
+            randomness: read_scalar(&mut reader)?,
+            asset_generator: note.asset_generator(),
+        };
+        let is_miners_fee = match reader.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(IronfishError::new(IronfishErrorKind::InvalidData)),
+        };
+
+        Ok(Self {
+            note,
+            value_commitment,
+            is_miners_fee,
+        })
+    }
+
+    /// Write the note and the randomness of the value commitment, so that the
+    /// proof can be created elsewhere.
+    pub(crate) fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.note.write(&mut writer)?;
+        writer.write_all(&self.value_commitment.randomness.to_bytes())?;
+        writer.write_u8(u8::from(self.is_miners_fee))?;
+
+        Ok(())
+    }
+
     /// Construct and return the committed [`OutputDescription`] for this receiving calculation.
     ///
     /// The [`OutputDescription`] is the publicly visible form of the new note, not
@@ -146,8 +184,8 @@ impl OutputDescription {
     /// This is the main entry-point when reconstructing a serialized
     /// transaction.
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
-        let proof = groth16::Proof::read(&mut reader)?;
-        let merkle_note = MerkleNote::read(&mut reader)?;
+        let proof = groth16::Proof::read(&mut reader).context("proof")?;
+        let merkle_note = MerkleNote::read(&mut reader).context("merkle_note")?;
 
         Ok(OutputDescription { proof, merkle_note })
     }
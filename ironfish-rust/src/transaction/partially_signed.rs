@@ -0,0 +1,244 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A container for handing an [`UnsignedTransaction`] to an offline signer.
+//!
+//! The descriptions of an unsigned transaction only contain commitments to
+//! the notes it creates, so a signer cannot tell from the transaction alone
+//! where the funds go. A [`PartiallySignedTransaction`] also carries the
+//! plaintext notes of the outputs, and the assets minted, burned or sent,
+//! and checks them against the commitments in the transaction before it is
+//! signed. The signer can then show exactly what is being approved.
+
+use crate::{
+    assets::{
+        asset::Asset,
+        asset_identifier::{AssetIdentifier, NATIVE_ASSET},
+    },
+    errors::{IronfishError, IronfishErrorKind},
+    note::Note,
+    serializing::bytes_to_hex,
+    transaction::{unsigned::UnsignedTransaction, Transaction},
+    SaplingKey,
+};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::{fmt, io};
+
+/// Identifies a serialized [`PartiallySignedTransaction`].
+const PARTIALLY_SIGNED_TRANSACTION_MAGIC: &[u8; 4] = b"IFPS";
+
+/// The version of the serialization format of [`PartiallySignedTransaction`].
+const PARTIALLY_SIGNED_TRANSACTION_FORMAT_VERSION: u8 = 1;
+
+/// The name displayed for the native asset.
+const NATIVE_ASSET_NAME: &str = "$IRON";
+
+/// An [`UnsignedTransaction`] along with the plaintext of what it does.
+pub struct PartiallySignedTransaction {
+    unsigned_transaction: UnsignedTransaction,
+
+    /// The note created by each output of the transaction, in the same order
+    /// as the outputs.
+    output_notes: Vec<Note>,
+
+    /// Assets that the transaction refers to, used to show their names.
+    /// Minted assets do not need to be included, since mint descriptions
+    /// contain the whole asset.
+    assets: Vec<Asset>,
+}
+
+impl PartiallySignedTransaction {
+    /// Wrap an unsigned transaction with the notes created by its outputs.
+    ///
+    /// Fails if the notes do not match the commitments of the outputs.
+    pub fn new(
+        unsigned_transaction: UnsignedTransaction,
+        output_notes: Vec<Note>,
+    ) -> Result<Self, IronfishError> {
+        let transaction = PartiallySignedTransaction {
+            unsigned_transaction,
+            output_notes,
+            assets: vec![],
+        };
+        transaction.verify()?;
+
+        Ok(transaction)
+    }
+
+    /// Include the details of an asset, so that its name can be shown for
+    /// the outputs and burns of that asset.
+    ///
+    /// The identifier of an [`Asset`] is always derived from its details, so
+    /// the name cannot be made up for another asset.
+    pub fn add_asset(&mut self, asset: Asset) {
+        if self.asset(asset.id()).is_none() {
+            self.assets.push(asset);
+        }
+    }
+
+    /// Check that the plaintext notes match the commitments of the outputs of
+    /// the transaction.
+    ///
+    /// The output proofs bind the value commitments to the note commitments,
+    /// so the values, assets and recipients of the notes are those of the
+    /// transaction once it is verified.
+    pub fn verify(&self) -> Result<(), IronfishError> {
+        let outputs = self.unsigned_transaction.outputs();
+        if outputs.len() != self.output_notes.len() {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidData));
+        }
+
+        for (output, note) in outputs.iter().zip(&self.output_notes) {
+            if output.merkle_note.note_commitment != note.commitment_point() {
+                return Err(IronfishError::new(IronfishErrorKind::InvalidCommitment));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verify the plaintext of the transaction, then sign it.
+    pub fn sign(&self, spender_key: &SaplingKey) -> Result<Transaction, IronfishError> {
+        self.verify()?;
+        self.unsigned_transaction.sign(spender_key)
+    }
+
+    pub fn unsigned_transaction(&self) -> &UnsignedTransaction {
+        &self.unsigned_transaction
+    }
+
+    pub fn output_notes(&self) -> &[Note] {
+        &self.output_notes
+    }
+
+    /// Find the details of an asset that the transaction refers to, either
+    /// because it was added with [`PartiallySignedTransaction::add_asset`] or
+    /// because it is minted.
+    pub fn asset(&self, asset_id: &AssetIdentifier) -> Option<&Asset> {
+        self.assets
+            .iter()
+            .chain(
+                self.unsigned_transaction
+                    .mints()
+                    .iter()
+                    .map(|mint| &mint.description().asset),
+            )
+            .find(|asset| asset.id() == asset_id)
+    }
+
+    /// The name of an asset, or its hex identifier if it is unknown.
+    fn asset_name(&self, asset_id: &AssetIdentifier) -> String {
+        if asset_id == &NATIVE_ASSET {
+            return NATIVE_ASSET_NAME.to_string();
+        }
+
+        match self.asset(asset_id) {
+            Some(asset) => String::from_utf8_lossy(asset.name())
+                .trim_end_matches('\0')
+                .to_string(),
+            None => bytes_to_hex(asset_id.as_bytes()),
+        }
+    }
+
+    /// Read a transaction written with [`PartiallySignedTransaction::write`],
+    /// checking its plaintext.
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PARTIALLY_SIGNED_TRANSACTION_MAGIC
+            || reader.read_u8()? != PARTIALLY_SIGNED_TRANSACTION_FORMAT_VERSION
+        {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidData));
+        }
+
+        let unsigned_transaction = UnsignedTransaction::read(&mut reader)?;
+
+        let num_notes = reader.read_u64::<LittleEndian>()?;
+        let mut output_notes = Vec::with_capacity(num_notes as usize);
+        for _ in 0..num_notes {
+            output_notes.push(Note::read(&mut reader)?);
+        }
+
+        let mut transaction = PartiallySignedTransaction::new(unsigned_transaction, output_notes)?;
+
+        let num_assets = reader.read_u64::<LittleEndian>()?;
+        for _ in 0..num_assets {
+            transaction.add_asset(Asset::read(&mut reader)?);
+        }
+
+        Ok(transaction)
+    }
+
+    pub fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_all(PARTIALLY_SIGNED_TRANSACTION_MAGIC)?;
+        writer.write_u8(PARTIALLY_SIGNED_TRANSACTION_FORMAT_VERSION)?;
+        self.unsigned_transaction.write(&mut writer)?;
+
+        writer.write_u64::<LittleEndian>(self.output_notes.len() as u64)?;
+        for note in &self.output_notes {
+            note.write(&mut writer)?;
+        }
+
+        writer.write_u64::<LittleEndian>(self.assets.len() as u64)?;
+        for asset in &self.assets {
+            asset.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for PartiallySignedTransaction {
+    /// A description of what the transaction does, one line per description,
+    /// for the signer to review.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Fee: {} {}",
+            self.unsigned_transaction.fee(),
+            NATIVE_ASSET_NAME
+        )?;
+        writeln!(f, "Expiration: {}", self.unsigned_transaction.expiration())?;
+
+        for note in &self.output_notes {
+            write!(
+                f,
+                "Send {} {} to {}",
+                note.value(),
+                self.asset_name(note.asset_id()),
+                note.owner().hex_public_address()
+            )?;
+            let memo = note.memo().to_string();
+            let memo = memo.trim_end_matches('\0');
+            if !memo.is_empty() {
+                write!(f, " with memo {:?}", memo)?;
+            }
+            writeln!(f)?;
+        }
+
+        for mint in self.unsigned_transaction.mints() {
+            let description = mint.description();
+            write!(
+                f,
+                "Mint {} {}",
+                description.value,
+                self.asset_name(description.asset.id())
+            )?;
+            if let Some(new_owner) = description.transfer_ownership_to {
+                write!(
+                    f,
+                    " and transfer ownership to {}",
+                    new_owner.hex_public_address()
+                )?;
+            }
+            writeln!(f)?;
+        }
+
+        for burn in self.unsigned_transaction.burns() {
+            writeln!(f, "Burn {} {}", burn.value, self.asset_name(&burn.asset_id))?;
+        }
+
+        Ok(())
+    }
+}
@@ -10,23 +10,26 @@ use crate::{
     errors::{IronfishError, IronfishErrorKind},
     keys::{PublicAddress, SaplingKey},
     note::Note,
+    serializing::read_scalar,
     transaction::{
-        burns::{BurnBuilder, BurnDescription},
+        burns::{BurnBuilder, BurnDescription, BURN_DESCRIPTION_SIZE},
         calculate_value_balance,
+        coin_selection::{select_notes, SelectionStrategy, SpendableNote},
         mints::MintDescription,
-        mints::{MintBuilder, UnsignedMintDescription},
-        outputs::{OutputBuilder, OutputDescription},
-        spends::{SpendBuilder, UnsignedSpendDescription},
+        mints::{mint_description_size, MintBuilder, UnsignedMintDescription},
+        outputs::{OutputBuilder, OutputDescription, OUTPUT_DESCRIPTION_SIZE},
+        partially_signed::PartiallySignedTransaction,
+        spends::{SpendBuilder, UnsignedSpendDescription, SPEND_DESCRIPTION_SIZE},
         unsigned::UnsignedTransaction,
         value_balances::ValueBalances,
-        Transaction, TransactionVersion, SIGNATURE_HASH_PERSONALIZATION,
+        Transaction, TransactionVersion, SIGNATURE_HASH_PERSONALIZATION, TRANSACTION_FIXED_SIZE,
         TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE, TRANSACTION_SIGNATURE_VERSION,
     },
     witness::WitnessTrait,
     OutgoingViewKey, ViewKey,
 };
 use blake2b_simd::Params as Blake2b;
-use byteorder::{LittleEndian, WriteBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use ff::Field;
 use group::GroupEncoding;
 use ironfish_jubjub::ExtendedPoint;
@@ -35,8 +38,36 @@ use ironfish_zkp::{
     redjubjub::{self, Signature},
     ProofGenerationKey,
 };
+use lazy_static::lazy_static;
 use rand::{rngs::OsRng, thread_rng};
-use std::io::Write;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    sync::Arc,
+};
+
+lazy_static! {
+    /// Thread pool used to create proofs when no pool was set with
+    /// [`ProposedTransaction::set_thread_pool`].
+    ///
+    /// Proofs must not be created on the global rayon pool: the prover blocks
+    /// while waiting on its own worker threads, which in turn run parallel
+    /// iterators on the global pool. Blocking every global worker on a proof
+    /// would deadlock.
+    static ref PROOF_THREAD_POOL: ThreadPool = ThreadPoolBuilder::new()
+        .thread_name(|index| format!("proof-{}", index))
+        .build()
+        .expect("failed to create the proof thread pool");
+}
+
+/// Identifies a serialized [`ProposedTransaction`].
+const PROPOSED_TRANSACTION_MAGIC: &[u8; 4] = b"IFPT";
+
+/// The version of the serialization format of [`ProposedTransaction`]. This
+/// is independent from the [`TransactionVersion`] of the transaction, and is
+/// incremented when the format changes.
+const PROPOSED_TRANSACTION_FORMAT_VERSION: u8 = 1;
 
 /// A collection of spend and output proofs that can be signed and verified.
 /// In general, all the spent values should add up to all the output values.
@@ -83,6 +114,10 @@ pub struct ProposedTransaction {
     // Used to add randomness to signature generation without leaking the
     // key. Referred to as `ar` in the literature.
     pub(super) public_key_randomness: ironfish_jubjub::Fr,
+
+    /// Thread pool used to create the proofs in parallel. When not set, a
+    /// pool shared by all proposed transactions is used.
+    thread_pool: Option<Arc<ThreadPool>>,
     // NOTE: If adding fields here, you may need to add fields to
     // signature hash method, and also to Transaction.
 }
@@ -98,9 +133,19 @@ impl ProposedTransaction {
             value_balances: ValueBalances::new(),
             expiration: 0,
             public_key_randomness: ironfish_jubjub::Fr::random(thread_rng()),
+            thread_pool: None,
         }
     }
 
+    /// Create the proofs of this transaction on the given thread pool, for
+    /// example to limit the number of threads used.
+    ///
+    /// The pool must not be the global rayon pool, which the prover itself
+    /// relies on.
+    pub fn set_thread_pool(&mut self, thread_pool: Arc<ThreadPool>) {
+        self.thread_pool = Some(thread_pool);
+    }
+
     /// Spend the note owned by spender_key at the given witness location.
     pub fn add_spend(
         &mut self,
@@ -115,6 +160,65 @@ impl ProposedTransaction {
         Ok(())
     }
 
+    /// Spend a note owned by one of the diversified addresses of spender_key,
+    /// see [`ViewKey::diversified_public_address`].
+    pub fn add_diversified_spend(
+        &mut self,
+        note: Note,
+        witness: &dyn WitnessTrait,
+        diversifier_index: u64,
+    ) -> Result<(), IronfishError> {
+        self.value_balances
+            .add(note.asset_id(), note.value().try_into()?)?;
+
+        let mut spend = SpendBuilder::new(note, witness);
+        spend.diversifier_index = diversifier_index;
+        self.spends.push(spend);
+
+        Ok(())
+    }
+
+    /// Choose notes from `pool` to cover the outputs, mints and burns added
+    /// so far and the fee, and add them as spends.
+    ///
+    /// At most `max_spends` spends are added, including the ones that were
+    /// already added to the transaction. Returns the indexes of the notes
+    /// spent in `pool`.
+    pub fn select_spends<W: WitnessTrait>(
+        &mut self,
+        pool: &[SpendableNote<W>],
+        intended_transaction_fee: u64,
+        max_spends: usize,
+        strategy: &dyn SelectionStrategy,
+    ) -> Result<Vec<usize>, IronfishError> {
+        let mut targets = HashMap::new();
+        for (asset_id, value) in self.value_balances.iter() {
+            let mut needed = -i128::from(*value);
+            if asset_id == &NATIVE_ASSET {
+                needed += i128::from(intended_transaction_fee);
+            }
+            if needed > 0 {
+                targets.insert(*asset_id, u64::try_from(needed)?);
+            }
+        }
+
+        let max_spends = max_spends
+            .checked_sub(self.spends.len())
+            .ok_or_else(|| IronfishError::new(IronfishErrorKind::TooManySpends))?;
+        let selected = select_notes(pool, &targets, max_spends, strategy)?;
+
+        for index in &selected {
+            let spendable = &pool[*index];
+            self.add_diversified_spend(
+                spendable.note.clone(),
+                &spendable.witness,
+                spendable.diversifier_index,
+            )?;
+        }
+
+        Ok(selected)
+    }
+
     /// Create a proof of a new note owned by the recipient in this
     /// transaction.
     pub fn add_output(&mut self, note: Note) -> Result<(), IronfishError> {
@@ -157,13 +261,66 @@ impl ProposedTransaction {
         Ok(())
     }
 
-    pub(super) fn add_change_notes(
-        &mut self,
-        change_goes_to: Option<PublicAddress>,
-        public_address: PublicAddress,
+    /// The exact size, in bytes, of the serialized transaction that posting
+    /// this transaction with the given fee would create, including the change
+    /// notes that would be added.
+    ///
+    /// No proof is created, so this can be used to pick a fee before posting
+    /// the transaction, see [`ProposedTransaction::estimate_fee`].
+    pub fn estimated_size(&self, intended_transaction_fee: u64) -> Result<usize, IronfishError> {
+        let change_notes = if self.is_miners_fee() {
+            0
+        } else {
+            self.change_amounts(i64::try_from(intended_transaction_fee)?)?
+                .len()
+        };
+
+        let mints_size = self
+            .mints
+            .iter()
+            .map(|mint| mint_description_size(self.version, mint.transfer_ownership_to.is_some()))
+            .sum::<usize>();
+
+        Ok(TRANSACTION_FIXED_SIZE
+            + self.spends.len() * SPEND_DESCRIPTION_SIZE
+            + (self.outputs.len() + change_notes) * OUTPUT_DESCRIPTION_SIZE
+            + mints_size
+            + self.burns.len() * BURN_DESCRIPTION_SIZE)
+    }
+
+    /// The fee to post this transaction with so that it pays at least
+    /// `rate_per_byte` for each byte of the serialized transaction.
+    ///
+    /// The native asset change is what pays for the fee. If the change left
+    /// after paying for the transaction with a change note is not positive,
+    /// the change note is dropped and the whole native balance is used as
+    /// the fee instead, since the transaction is then smaller.
+    ///
+    /// The spends must already cover the fee: if they do not, spend more
+    /// notes, for example with [`ProposedTransaction::select_spends`], and
+    /// estimate the fee again.
+    pub fn estimate_fee(&self, rate_per_byte: u64) -> Result<u64, IronfishError> {
+        let native_balance = u64::try_from((*self.value_balances.fee()).max(0))?;
+
+        let fee = fee_for_size(self.estimated_size(0)?, rate_per_byte)?;
+        if fee < native_balance {
+            return Ok(fee);
+        }
+
+        let fee_without_change = fee_for_size(self.estimated_size(native_balance)?, rate_per_byte)?;
+        if fee_without_change > native_balance {
+            return Err(IronfishError::new(IronfishErrorKind::InsufficientFunds));
+        }
+
+        Ok(native_balance)
+    }
+
+    /// The value of the change notes for each asset, once the fee is paid.
+    fn change_amounts(
+        &self,
         intended_transaction_fee: i64,
-    ) -> Result<(), IronfishError> {
-        let mut change_notes = vec![];
+    ) -> Result<Vec<(AssetIdentifier, u64)>, IronfishError> {
+        let mut change_amounts = vec![];
 
         for (asset_id, value) in self.value_balances.iter() {
             let is_native_asset = asset_id == &NATIVE_ASSET;
@@ -177,24 +334,40 @@ impl ProposedTransaction {
                 return Err(IronfishError::new(IronfishErrorKind::InvalidBalance));
             }
             if change_amount > 0 {
-                let change_address = change_goes_to.unwrap_or(public_address);
-                let change_note = Note::new(
-                    change_address,
-                    change_amount as u64, // we checked it was positive
-                    "",
-                    *asset_id,
-                    public_address,
-                );
-
-                change_notes.push(change_note);
+                // we checked it was positive
+                change_amounts.push((*asset_id, change_amount as u64));
             }
         }
-        for change_note in change_notes {
+
+        Ok(change_amounts)
+    }
+
+    pub(super) fn add_change_notes(
+        &mut self,
+        change_goes_to: Option<PublicAddress>,
+        public_address: PublicAddress,
+        intended_transaction_fee: i64,
+    ) -> Result<(), IronfishError> {
+        let change_address = change_goes_to.unwrap_or(public_address);
+
+        for (asset_id, change_amount) in self.change_amounts(intended_transaction_fee)? {
+            let change_note =
+                Note::new(change_address, change_amount, "", asset_id, public_address);
             self.add_output(change_note)?;
         }
         Ok(())
     }
 
+    fn is_miners_fee(&self) -> bool {
+        self.outputs.iter().any(|output| output.get_is_miners_fee())
+    }
+
+    /// Create the proofs for every description and return the transaction,
+    /// ready to be signed.
+    ///
+    /// Proofs are generated in parallel, see
+    /// [`ProposedTransaction::set_thread_pool`]. The order of the descriptions
+    /// in the transaction does not depend on the order in which proofs finish.
     pub fn build(
         &mut self,
         proof_authorizing_key: ironfish_jubjub::Fr,
@@ -209,8 +382,7 @@ impl ProposedTransaction {
             ProofGenerationKey::new(view_key.authorizing_key, proof_authorizing_key);
 
         // skip adding change notes if this is special case of a miners fee transaction
-        let is_miners_fee = self.outputs.iter().any(|output| output.get_is_miners_fee());
-        if !is_miners_fee {
+        if !self.is_miners_fee() {
             self.add_change_notes(change_goes_to, public_address, intended_transaction_fee)?;
         }
 
@@ -220,35 +392,60 @@ impl ProposedTransaction {
         let randomized_public_key = redjubjub::PublicKey(view_key.authorizing_key.into())
             .randomize(self.public_key_randomness, *SPENDING_KEY_GENERATOR);
 
-        let mut unsigned_spends = Vec::with_capacity(self.spends.len());
-        for spend in &self.spends {
-            unsigned_spends.push(spend.build(
-                &proof_generation_key,
-                &view_key,
-                &self.public_key_randomness,
-                &randomized_public_key,
-            )?);
-        }
-
-        let mut output_descriptions = Vec::with_capacity(self.outputs.len());
-        for output in &self.outputs {
-            output_descriptions.push(output.build(
-                &proof_generation_key,
-                &outgoing_view_key,
-                &self.public_key_randomness,
-                &randomized_public_key,
-            )?);
-        }
-
-        let mut unsigned_mints = Vec::with_capacity(self.mints.len());
-        for mint in &self.mints {
-            unsigned_mints.push(mint.build(
-                &proof_generation_key,
-                &public_address,
-                &self.public_key_randomness,
-                &randomized_public_key,
-            )?);
-        }
+        // Proofs are independent of each other, so they are all created
+        // concurrently. Collecting the results preserves the order in which
+        // the descriptions were added.
+        let thread_pool = self.thread_pool.as_deref().unwrap_or(&PROOF_THREAD_POOL);
+        let (unsigned_spends, (output_descriptions, unsigned_mints)) = thread_pool.install(|| {
+            rayon::join(
+                || {
+                    self.spends
+                        .par_iter()
+                        .map(|spend| {
+                            spend.build(
+                                &proof_generation_key,
+                                &view_key,
+                                &self.public_key_randomness,
+                                &randomized_public_key,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+                || {
+                    rayon::join(
+                        || {
+                            self.outputs
+                                .par_iter()
+                                .map(|output| {
+                                    output.build(
+                                        &proof_generation_key,
+                                        &outgoing_view_key,
+                                        &self.public_key_randomness,
+                                        &randomized_public_key,
+                                    )
+                                })
+                                .collect::<Result<Vec<_>, _>>()
+                        },
+                        || {
+                            self.mints
+                                .par_iter()
+                                .map(|mint| {
+                                    mint.build(
+                                        &proof_generation_key,
+                                        &public_address,
+                                        &self.public_key_randomness,
+                                        &randomized_public_key,
+                                    )
+                                })
+                                .collect::<Result<Vec<_>, _>>()
+                        },
+                    )
+                },
+            )
+        });
+        let unsigned_spends = unsigned_spends?;
+        let output_descriptions = output_descriptions?;
+        let unsigned_mints = unsigned_mints?;
 
         let mut burn_descriptions = Vec::with_capacity(self.burns.len());
         for burn in &self.burns {
@@ -287,6 +484,33 @@ impl ProposedTransaction {
         })
     }
 
+    /// Build the transaction like [`ProposedTransaction::build`], along with
+    /// the plaintext of the notes it creates, including the change notes, so
+    /// that it can be reviewed and signed offline.
+    pub fn build_partially_signed(
+        &mut self,
+        proof_authorizing_key: ironfish_jubjub::Fr,
+        view_key: ViewKey,
+        outgoing_view_key: OutgoingViewKey,
+        intended_transaction_fee: i64,
+        change_goes_to: Option<PublicAddress>,
+    ) -> Result<PartiallySignedTransaction, IronfishError> {
+        let unsigned_transaction = self.build(
+            proof_authorizing_key,
+            view_key,
+            outgoing_view_key,
+            intended_transaction_fee,
+            change_goes_to,
+        )?;
+        let output_notes = self
+            .outputs
+            .iter()
+            .map(|output| output.note.clone())
+            .collect();
+
+        PartiallySignedTransaction::new(unsigned_transaction, output_notes)
+    }
+
     /// Post the transaction. This performs a bit of validation, and signs
     /// the spends with a signature that proves the spends are part of this
     /// transaction.
@@ -355,6 +579,98 @@ impl ProposedTransaction {
         unsigned.sign(spender_key)
     }
 
+    /// Read a transaction written with [`ProposedTransaction::write`].
+    pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != PROPOSED_TRANSACTION_MAGIC
+            || reader.read_u8()? != PROPOSED_TRANSACTION_FORMAT_VERSION
+        {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidData));
+        }
+
+        let version = TransactionVersion::read(&mut reader)?;
+        let mut transaction = ProposedTransaction::new(version);
+        transaction.expiration = reader.read_u32::<LittleEndian>()?;
+        transaction.public_key_randomness = read_scalar(&mut reader)?;
+
+        let num_spends = reader.read_u64::<LittleEndian>()?;
+        let num_outputs = reader.read_u64::<LittleEndian>()?;
+        let num_mints = reader.read_u64::<LittleEndian>()?;
+        let num_burns = reader.read_u64::<LittleEndian>()?;
+
+        for _ in 0..num_spends {
+            let spend = SpendBuilder::read(&mut reader)?;
+            transaction
+                .value_balances
+                .add(spend.note.asset_id(), spend.note.value().try_into()?)?;
+            transaction.spends.push(spend);
+        }
+
+        for _ in 0..num_outputs {
+            let output = OutputBuilder::read(&mut reader)?;
+            transaction
+                .value_balances
+                .subtract(output.note.asset_id(), output.note.value().try_into()?)?;
+            transaction.outputs.push(output);
+        }
+
+        for _ in 0..num_mints {
+            let mint = MintBuilder::read(&mut reader)?;
+            transaction
+                .value_balances
+                .add(mint.asset.id(), mint.value.try_into()?)?;
+            transaction.mints.push(mint);
+        }
+
+        for _ in 0..num_burns {
+            let burn = BurnBuilder::read(&mut reader)?;
+            transaction
+                .value_balances
+                .subtract(&burn.asset_id, burn.value.try_into()?)?;
+            transaction.burns.push(burn);
+        }
+
+        Ok(transaction)
+    }
+
+    /// Store the transaction before any proof is created, so that it can be
+    /// built on another machine, for example by a separate prover.
+    ///
+    /// The output contains the notes being spent and created, along with the
+    /// witnesses of the spent notes, and must be kept private. The thread pool
+    /// set with [`ProposedTransaction::set_thread_pool`] is not stored.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        writer.write_all(PROPOSED_TRANSACTION_MAGIC)?;
+        writer.write_u8(PROPOSED_TRANSACTION_FORMAT_VERSION)?;
+        self.version.write(&mut writer)?;
+        writer.write_u32::<LittleEndian>(self.expiration)?;
+        writer.write_all(&self.public_key_randomness.to_bytes())?;
+
+        writer.write_u64::<LittleEndian>(self.spends.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.outputs.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.mints.len() as u64)?;
+        writer.write_u64::<LittleEndian>(self.burns.len() as u64)?;
+
+        for spend in &self.spends {
+            spend.write(&mut writer)?;
+        }
+
+        for output in &self.outputs {
+            output.write(&mut writer)?;
+        }
+
+        for mint in &self.mints {
+            mint.write(&mut writer)?;
+        }
+
+        for burn in &self.burns {
+            burn.write(&mut writer)?;
+        }
+
+        Ok(())
+    }
+
     /// Get the expiration sequence for this transaction
     pub fn expiration(&self) -> u32 {
         self.expiration
@@ -499,3 +815,10 @@ impl ProposedTransaction {
         )
     }
 }
+
+/// The fee paid by a transaction of `size` bytes at `rate_per_byte`.
+fn fee_for_size(size: usize, rate_per_byte: u64) -> Result<u64, IronfishError> {
+    u64::try_from(size)?
+        .checked_mul(rate_per_byte)
+        .ok_or_else(|| IronfishError::new(IronfishErrorKind::IllegalValue))
+}
@@ -3,10 +3,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    errors::{IronfishError, IronfishErrorKind},
+    errors::{ErrorContext, IronfishError, IronfishErrorKind},
     keys::SaplingKey,
     serializing::{read_point, read_scalar},
-    transaction::TRANSACTION_PUBLIC_KEY_SIZE,
+    transaction::{outputs::PROOF_SIZE, TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE},
 };
 use blstrs::{Bls12, Scalar};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
@@ -26,6 +26,7 @@ use std::io;
 use crate::transaction::verify::verify_spend_proof;
 #[cfg(feature = "transaction-proofs")]
 use crate::{
+    keys::DEFAULT_DIVERSIFIER_INDEX,
     merkle_note::{position as witness_position, sapling_auth_path},
     note::Note,
     sapling_bls12::SAPLING,
@@ -58,6 +59,10 @@ pub struct SpendBuilder {
     pub(crate) tree_size: u32,
     pub(crate) witness_position: u64,
     pub(crate) auth_path: Vec<Option<(Scalar, bool)>>,
+
+    /// The diversifier index of the address that owns the note, see
+    /// [`crate::keys::DEFAULT_DIVERSIFIER_INDEX`].
+    pub(crate) diversifier_index: u64,
 }
 
 #[cfg(feature = "transaction-proofs")]
@@ -78,6 +83,7 @@ impl SpendBuilder {
             tree_size: witness.tree_size(),
             witness_position: witness_position(witness),
             auth_path: sapling_auth_path(witness),
+            diversifier_index: DEFAULT_DIVERSIFIER_INDEX,
         }
     }
 
@@ -89,6 +95,76 @@ impl SpendBuilder {
         ExtendedPoint::from(self.value_commitment.commitment())
     }
 
+    /// Read a [`SpendBuilder`] written with [`SpendBuilder::write`].
+    pub(crate) fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
+        let note = Note::read(&mut reader)?;
+        let value_commitment = ValueCommitment {
+            value: note.value,
+            randomness: read_scalar(&mut reader)?,
+            asset_generator: note.asset_generator(),
+        };
+        let root_hash = read_scalar(&mut reader)?;
+        let tree_size = reader.read_u32::<LittleEndian>()?;
+        let witness_position = reader.read_u64::<LittleEndian>()?;
+
+        let auth_path_length = reader.read_u8()?;
+        let mut auth_path = Vec::with_capacity(auth_path_length as usize);
+        for _ in 0..auth_path_length {
+            let element = match reader.read_u8()? {
+                0 => None,
+                1 => {
+                    let hash = read_scalar(&mut reader)?;
+                    let is_right = match reader.read_u8()? {
+                        0 => false,
+                        1 => true,
+                        _ => return Err(IronfishError::new(IronfishErrorKind::InvalidData)),
+                    };
+                    Some((hash, is_right))
+                }
+                _ => return Err(IronfishError::new(IronfishErrorKind::InvalidData)),
+            };
+            auth_path.push(element);
+        }
+
+        let diversifier_index = reader.read_u64::<LittleEndian>()?;
+
+        Ok(SpendBuilder {
+            note,
+            value_commitment,
+            root_hash,
+            tree_size,
+            witness_position,
+            auth_path,
+            diversifier_index,
+        })
+    }
+
+    /// Write the note, its witness and the randomness of the value
+    /// commitment, so that the proof can be created elsewhere.
+    pub(crate) fn write<W: io::Write>(&self, mut writer: W) -> Result<(), IronfishError> {
+        self.note.write(&mut writer)?;
+        writer.write_all(&self.value_commitment.randomness.to_bytes())?;
+        writer.write_all(self.root_hash.to_repr().as_ref())?;
+        writer.write_u32::<LittleEndian>(self.tree_size)?;
+        writer.write_u64::<LittleEndian>(self.witness_position)?;
+
+        writer.write_u8(u8::try_from(self.auth_path.len())?)?;
+        for element in &self.auth_path {
+            match element {
+                None => writer.write_u8(0)?,
+                Some((hash, is_right)) => {
+                    writer.write_u8(1)?;
+                    writer.write_all(hash.to_repr().as_ref())?;
+                    writer.write_u8(u8::from(*is_right))?;
+                }
+            }
+        }
+
+        writer.write_u64::<LittleEndian>(self.diversifier_index)?;
+
+        Ok(())
+    }
+
     /// Sign this spend with the private key, and return a [`SpendDescription`]
     /// suitable for serialization.
     ///
@@ -103,14 +179,24 @@ impl SpendBuilder {
     ) -> Result<UnsignedSpendDescription, IronfishError> {
         let value_commitment_point = self.value_commitment_point();
 
+        // Notes owned by a diversified address are proven against the
+        // authorizing key of that address. The randomness is adjusted so that
+        // the randomized public key, and therefore the signing key, are the
+        // same as for the default address.
+        let tweak = view_key.diversifier_tweak(self.diversifier_index);
+        let proof_generation_key = ProofGenerationKey::new(
+            view_key.diversified_authorizing_key(self.diversifier_index),
+            proof_generation_key.nsk,
+        );
+
         let circuit = Spend {
             value_commitment: Some(self.value_commitment.clone()),
-            proof_generation_key: Some(proof_generation_key.clone()),
+            proof_generation_key: Some(proof_generation_key),
             payment_address: Some(self.note.owner.0),
             auth_path: self.auth_path.clone(),
             commitment_randomness: Some(self.note.randomness),
             anchor: Some(self.root_hash),
-            ar: Some(*public_key_randomness),
+            ar: Some(public_key_randomness - tweak),
             sender_address: Some(self.note.sender.0),
         };
 
@@ -222,6 +308,17 @@ impl UnsignedSpendDescription {
     }
 }
 
+/// Size of a serialized [`SpendDescription`].
+//   192 proof
+// + 32  value commitment
+// + 32  root hash
+// + 4   tree size
+// + 32  nullifier
+// + 64  authorizing signature
+// = 356
+pub const SPEND_DESCRIPTION_SIZE: usize =
+    PROOF_SIZE as usize + 32 + 32 + 4 + 32 + TRANSACTION_SIGNATURE_SIZE;
+
 /// The publicly visible value of a spent note. These get serialized to prove
 /// that the owner once had access to these values. It also publishes the
 /// nullifier so that they can't pretend they still have access to them.
@@ -262,13 +359,14 @@ impl SpendDescription {
     /// file) This is the main entry-point when reconstructing a serialized
     /// transaction.
     pub fn read<R: io::Read>(mut reader: R) -> Result<Self, IronfishError> {
-        let proof = groth16::Proof::read(&mut reader)?;
-        let value_commitment = read_point(&mut reader)?;
-        let root_hash = read_scalar(&mut reader)?;
-        let tree_size = reader.read_u32::<LittleEndian>()?;
+        let proof = groth16::Proof::read(&mut reader).context("proof")?;
+        let value_commitment = read_point(&mut reader).context("value_commitment")?;
+        let root_hash = read_scalar(&mut reader).context("root_hash")?;
+        let tree_size = reader.read_u32::<LittleEndian>().context("tree_size")?;
         let mut nullifier = Nullifier([0; 32]);
-        reader.read_exact(&mut nullifier.0)?;
-        let authorizing_signature = redjubjub::Signature::read(&mut reader)?;
+        reader.read_exact(&mut nullifier.0).context("nullifier")?;
+        let authorizing_signature =
+            redjubjub::Signature::read(&mut reader).context("authorizing_signature")?;
 
         Ok(SpendDescription {
             proof,
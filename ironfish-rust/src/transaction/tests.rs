@@ -11,16 +11,29 @@ use crate::{
 use crate::{
     assets::{asset::Asset, asset_identifier::NATIVE_ASSET},
     frost_utils::{account_keys::derive_account_keys, split_spender_key::split_spender_key},
-    keys::SaplingKey,
-    merkle_note::NOTE_ENCRYPTION_MINER_KEYS,
+    keys::{SaplingKey, ViewKeys},
+    merkle_note::{position, NOTE_ENCRYPTION_MINER_KEYS},
+    merkle_tree::CommitmentTree,
     note::Note,
     sapling_bls12::SAPLING,
     test_util::{create_multisig_identities, make_fake_witness},
     transaction::{
-        verify::batch_verify_transactions, verify::internal_batch_verify_transactions,
-        verify_transaction, ProposedTransaction, TransactionVersion, TRANSACTION_EXPIRATION_SIZE,
-        TRANSACTION_FEE_SIZE, TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE,
+        burns::BurnDescription,
+        coin_selection::{LargestFirst, SpendableNote},
+        mints::MintDescription,
+        outputs::{OUTPUT_DESCRIPTION_SIZE, PROOF_SIZE},
+        partially_signed::PartiallySignedTransaction,
+        verify::batch_verify_transactions,
+        verify::batch_verify_transactions_with_failures,
+        verify::internal_batch_verify_transactions,
+        verify::internal_batch_verify_transactions_with_failures,
+        verify_transaction,
+        view::TransactionView,
+        ProposedTransaction, TransactionId, TransactionVerificationFailure, TransactionVersion,
+        TRANSACTION_EXPIRATION_SIZE, TRANSACTION_FEE_SIZE, TRANSACTION_FIXED_SIZE,
+        TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE,
     },
+    MerkleNoteHash, SaplingVerifier,
 };
 #[cfg(feature = "transaction-proofs")]
 use ff::Field;
@@ -45,6 +58,8 @@ use ironfish_zkp::{
 use rand::thread_rng;
 #[cfg(feature = "transaction-proofs")]
 use std::collections::{BTreeMap, HashMap};
+#[cfg(feature = "transaction-proofs")]
+use std::sync::Arc;
 
 #[test]
 #[cfg(feature = "transaction-proofs")]
@@ -219,6 +234,802 @@ fn test_transaction_simple() {
     assert_eq!(received_note.sender, spender_key_clone.public_address());
 }
 
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_spend_from_diversified_address() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let diversifier_index = 3;
+    let diversified_address = spender_key
+        .view_key()
+        .diversified_public_address(diversifier_index)
+        .unwrap();
+
+    let in_note = Note::new(
+        diversified_address,
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let out_note = Note::new(
+        receiver_key.public_address(),
+        40,
+        "",
+        NATIVE_ASSET,
+        spender_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    // Spending the note as if it was owned by the default address fails
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note.clone(), &witness).unwrap();
+    transaction.add_output(out_note.clone()).unwrap();
+    assert!(transaction.post(&spender_key, None, 1).is_err());
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction
+        .add_diversified_spend(in_note.clone(), &witness, diversifier_index)
+        .unwrap();
+    transaction.add_output(out_note).unwrap();
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+
+    assert_eq!(
+        public_transaction.spends[0].nullifier,
+        in_note.nullifier(spender_key.view_key(), position(&witness))
+    );
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_select_spends() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    let pool = [10, 25, 7]
+        .into_iter()
+        .map(|value| {
+            let note = Note::new(
+                spender_key.public_address(),
+                value,
+                "",
+                NATIVE_ASSET,
+                receiver_key.public_address(),
+            );
+            let witness = make_fake_witness(&note);
+            SpendableNote::new(note, witness)
+        })
+        .collect::<Vec<_>>();
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+
+    let err = transaction
+        .select_spends(&pool, 2, 1, &LargestFirst)
+        .unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::TooManySpends);
+    assert_eq!(transaction.spends.len(), 0);
+
+    let selected = transaction
+        .select_spends(&pool, 2, 2, &LargestFirst)
+        .expect("should be able to select spends");
+    assert_eq!(selected, vec![0, 1]);
+    assert_eq!(transaction.spends.len(), 2);
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 2)
+        .expect("should be able to post transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+    assert_eq!(public_transaction.fee(), 2);
+
+    // The change note holds the remaining 3
+    assert_eq!(public_transaction.outputs.len(), 2);
+    let change_note = public_transaction.outputs[1]
+        .merkle_note()
+        .decrypt_note_for_owner(spender_key.incoming_view_key())
+        .unwrap();
+    assert_eq!(change_note.value(), 3);
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_estimated_size() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        10_000,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    for version in [TransactionVersion::V1, TransactionVersion::V2] {
+        let mut transaction = ProposedTransaction::new(version);
+        transaction.add_spend(in_note.clone(), &witness).unwrap();
+        transaction
+            .add_output(Note::new(
+                receiver_key.public_address(),
+                30,
+                "",
+                NATIVE_ASSET,
+                spender_key.public_address(),
+            ))
+            .unwrap();
+        if version.has_mint_transfer_ownership_to() {
+            transaction
+                .add_mint_with_new_owner(asset, 10, receiver_key.public_address())
+                .unwrap();
+        } else {
+            transaction.add_mint(asset, 10).unwrap();
+        }
+        transaction.add_burn(*asset.id(), 3).unwrap();
+
+        let fee = transaction.estimate_fee(2).unwrap();
+        let estimated_size = transaction.estimated_size(fee).unwrap();
+        assert_eq!(fee, estimated_size as u64 * 2);
+
+        let public_transaction = transaction
+            .post(&spender_key, None, fee)
+            .expect("should be able to post transaction");
+
+        // One change note for each asset
+        assert_eq!(public_transaction.outputs.len(), 3);
+
+        let mut serialized = vec![];
+        public_transaction.write(&mut serialized).unwrap();
+        assert_eq!(serialized.len(), estimated_size);
+    }
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_estimate_fee_adjusts_change() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        3000,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let proposed_transaction = |native_balance: u64| {
+        let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+        transaction.add_spend(in_note.clone(), &witness).unwrap();
+        transaction
+            .add_output(Note::new(
+                receiver_key.public_address(),
+                3000 - native_balance,
+                "",
+                NATIVE_ASSET,
+                spender_key.public_address(),
+            ))
+            .unwrap();
+        transaction
+    };
+
+    let transaction = proposed_transaction(2000);
+    let size_with_change = transaction.estimated_size(0).unwrap();
+    let size_without_change = transaction.estimated_size(2000).unwrap();
+    assert_eq!(
+        size_with_change,
+        size_without_change + OUTPUT_DESCRIPTION_SIZE
+    );
+
+    // The change pays for the fee
+    assert_eq!(
+        transaction.estimate_fee(1).unwrap(),
+        size_with_change as u64
+    );
+
+    // The change left after paying for a change note is not positive, so the
+    // whole balance is used as the fee
+    let transaction = proposed_transaction(size_with_change as u64);
+    assert_eq!(
+        transaction.estimate_fee(1).unwrap(),
+        size_with_change as u64
+    );
+    let transaction = proposed_transaction(1200);
+    assert_eq!(transaction.estimate_fee(1).unwrap(), 1200);
+    assert_eq!(
+        transaction.estimated_size(1200).unwrap(),
+        size_without_change
+    );
+
+    let transaction = proposed_transaction(size_without_change as u64 - 1);
+    let err = transaction.estimate_fee(1).unwrap_err();
+    assert_eq!(err.kind, IronfishErrorKind::InsufficientFunds);
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_proposed_transaction_read_write() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "memo",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+    transaction
+        .add_mint_with_new_owner(asset, 10, receiver_key.public_address())
+        .unwrap();
+    transaction.add_burn(*asset.id(), 3).unwrap();
+    transaction.set_expiration(1234);
+
+    let mut serialized = vec![];
+    transaction.write(&mut serialized).unwrap();
+
+    let mut read_back = ProposedTransaction::read(&serialized[..]).unwrap();
+    assert_eq!(read_back.expiration(), 1234);
+    assert_eq!(
+        read_back.public_key_randomness,
+        transaction.public_key_randomness
+    );
+    assert_eq!(
+        read_back.estimated_size(1).unwrap(),
+        transaction.estimated_size(1).unwrap()
+    );
+
+    let mut reserialized = vec![];
+    read_back.write(&mut reserialized).unwrap();
+    assert_eq!(serialized, reserialized);
+
+    // The transaction read back can be posted
+    let public_transaction = read_back
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+    assert_eq!(public_transaction.expiration(), 1234);
+
+    serialized[0] ^= 1;
+    let err = ProposedTransaction::read(&serialized[..])
+        .err()
+        .expect("should not read a transaction with the wrong magic");
+    assert_eq!(err.kind, IronfishErrorKind::InvalidData);
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_partially_signed_transaction() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "for the fish",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+    transaction.add_mint(asset, 10).unwrap();
+    transaction.add_burn(*asset.id(), 10).unwrap();
+    transaction.set_expiration(1234);
+
+    let partially_signed = transaction
+        .build_partially_signed(
+            spender_key.proof_authorizing_key,
+            spender_key.view_key().clone(),
+            spender_key.outgoing_view_key().clone(),
+            2,
+            None,
+        )
+        .expect("should be able to build transaction");
+
+    // The output and the native change note
+    assert_eq!(partially_signed.output_notes().len(), 2);
+    assert_eq!(partially_signed.output_notes()[1].value(), 10);
+    assert_eq!(
+        partially_signed.to_string(),
+        format!(
+            "Fee: 2 $IRON\n\
+             Expiration: 1234\n\
+             Send 30 $IRON to {} with memo \"for the fish\"\n\
+             Send 10 $IRON to {}\n\
+             Mint 10 Testcoin\n\
+             Burn 10 Testcoin\n",
+            receiver_key.public_address().hex_public_address(),
+            spender_key.public_address().hex_public_address(),
+        )
+    );
+
+    let mut serialized = vec![];
+    partially_signed.write(&mut serialized).unwrap();
+    let read_back = PartiallySignedTransaction::read(&serialized[..]).unwrap();
+    assert_eq!(read_back.to_string(), partially_signed.to_string());
+
+    let public_transaction = read_back
+        .sign(&spender_key)
+        .expect("should be able to sign transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+
+    // Notes that do not match the outputs are rejected
+    let mut output_notes = partially_signed.output_notes().to_vec();
+    output_notes.swap(0, 1);
+    let err = PartiallySignedTransaction::new(
+        partially_signed.unsigned_transaction().clone(),
+        output_notes,
+    )
+    .err()
+    .expect("should not accept notes that do not match the outputs");
+    assert_eq!(err.kind, IronfishErrorKind::InvalidCommitment);
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_inspect() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let other_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+    let other_note = Note::new(
+        spender_key.public_address(),
+        5,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note.clone(), &witness).unwrap();
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+    transaction.add_mint(asset, 10).unwrap();
+    transaction.add_burn(*asset.id(), 3).unwrap();
+    transaction.set_expiration(1234);
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 2)
+        .expect("should be able to post transaction");
+
+    let keys = [
+        ViewKeys::from(&spender_key),
+        ViewKeys::from(&receiver_key),
+        ViewKeys::from(&other_key),
+    ];
+    let notes = [(other_note, 0), (in_note, position(&witness))];
+    let summary = public_transaction.inspect(&keys, &notes);
+
+    assert_eq!(summary.fee, 2);
+    assert_eq!(summary.expiration, 1234);
+
+    // The receiver gets the output, the spender gets both change notes
+    let received = summary
+        .received
+        .iter()
+        .map(|m| (m.note_index, m.key_index, m.note.value()))
+        .collect::<Vec<_>>();
+    assert_eq!(received.len(), 3);
+    assert!(received.contains(&(0, 1, 30)));
+    assert!(received
+        .iter()
+        .all(|(index, key, _)| *index == 0 || *key == 0));
+
+    // Every output is sent by the spender
+    assert_eq!(summary.sent.len(), 3);
+    assert!(summary.sent.iter().all(|m| m.key_index == 0));
+
+    assert_eq!(summary.spent.len(), 1);
+    assert_eq!(summary.spent[0].spend_index, 0);
+    assert_eq!(summary.spent[0].key_index, 0);
+    assert_eq!(summary.spent[0].note_index, 1);
+
+    assert_eq!(summary.mints.len(), 1);
+    assert_eq!(summary.mints[0].asset_id, *asset.id());
+    assert_eq!(summary.mints[0].value, 10);
+    assert_eq!(summary.burns.len(), 1);
+    assert_eq!(summary.burns[0].asset.unwrap().id(), asset.id());
+    assert_eq!(summary.burns[0].value, 3);
+
+    let spender_changes = summary.balance_changes(0);
+    assert_eq!(spender_changes.len(), 2);
+    assert_eq!(spender_changes[&NATIVE_ASSET], -32);
+    assert_eq!(spender_changes[asset.id()], 7);
+
+    let receiver_changes = summary.balance_changes(1);
+    assert_eq!(receiver_changes.len(), 1);
+    assert_eq!(receiver_changes[&NATIVE_ASSET], 30);
+
+    assert!(summary.balance_changes(2).is_empty());
+}
+
+#[test]
+#[cfg(all(feature = "transaction-proofs", feature = "serde"))]
+fn test_transaction_json_round_trip() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::V2);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "memo",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+    transaction
+        .add_mint_with_new_owner(asset, 10, receiver_key.public_address())
+        .unwrap();
+    transaction.add_burn(*asset.id(), 3).unwrap();
+    transaction.set_expiration(1234);
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 2)
+        .expect("should be able to post transaction");
+
+    let json = serde_json::to_value(&public_transaction).unwrap();
+    assert_eq!(json["version"], 2);
+    assert_eq!(json["fee"], "2");
+    assert_eq!(json["expiration"], 1234);
+    assert_eq!(
+        json["spends"][0]["nullifier"],
+        crate::serializing::bytes_to_hex(&public_transaction.spends()[0].nullifier().0)
+    );
+    assert_eq!(
+        json["mints"][0]["transferOwnershipTo"],
+        receiver_key.public_address().hex_public_address()
+    );
+
+    // The JSON and binary formats should describe the same transaction
+    let deserialized: Transaction = serde_json::from_value(json.clone()).unwrap();
+    let mut expected = vec![];
+    public_transaction.write(&mut expected).unwrap();
+    let mut actual = vec![];
+    deserialized.write(&mut actual).unwrap();
+    assert_eq!(actual, expected);
+    verify_transaction(&deserialized).expect("deserialized transaction should verify");
+
+    // Version 1 transactions cannot transfer the ownership of assets
+    let mut v1_json = json.clone();
+    v1_json["version"] = 1.into();
+    assert!(serde_json::from_value::<Transaction>(v1_json).is_err());
+
+    // Points must be valid
+    let mut invalid_json = json;
+    invalid_json["outputs"][0]["merkleNote"]["ephemeralPublicKey"] = "ff".repeat(32).into();
+    assert!(serde_json::from_value::<Transaction>(invalid_json).is_err());
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_read_error_context() {
+    let spender_key = SaplingKey::generate_key();
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        spender_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note, &witness).unwrap();
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+
+    let mut serialized = vec![];
+    public_transaction.write(&mut serialized).unwrap();
+
+    // Corrupt the value commitment of the spend, which follows its proof
+    let mut corrupted = serialized.clone();
+    let offset = TRANSACTION_FIXED_SIZE - TRANSACTION_SIGNATURE_SIZE + PROOF_SIZE as usize;
+    corrupted[offset..offset + 32].fill(0xff);
+    let error = Transaction::read(&corrupted[..]).unwrap_err();
+    assert_eq!(error.kind, IronfishErrorKind::InvalidData);
+    assert_eq!(error.context_path().unwrap(), "spends[0].value_commitment");
+
+    // Truncate the transaction in the middle of its output
+    let truncated = &serialized[..serialized.len() - TRANSACTION_SIGNATURE_SIZE - 10];
+    let error = Transaction::read(truncated).unwrap_err();
+    assert_eq!(error.kind, IronfishErrorKind::Io);
+    assert_eq!(
+        error.context_path().unwrap(),
+        "outputs[0].merkle_note.note_encryption_keys"
+    );
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_view() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let asset = Asset::new(spender_key.public_address(), "Testcoin", "A metadata").unwrap();
+    let other_asset = Asset::new(spender_key.public_address(), "Othercoin", "").unwrap();
+
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::V2);
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction
+        .add_output(Note::new(
+            receiver_key.public_address(),
+            30,
+            "",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        ))
+        .unwrap();
+    transaction
+        .add_mint_with_new_owner(asset, 10, receiver_key.public_address())
+        .unwrap();
+    transaction.add_mint(other_asset, 5).unwrap();
+    transaction.add_burn(*asset.id(), 3).unwrap();
+    transaction.set_expiration(1234);
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 2)
+        .expect("should be able to post transaction");
+
+    let mut serialized = vec![];
+    public_transaction.write(&mut serialized).unwrap();
+    let transaction_size = serialized.len();
+    // Trailing bytes are not part of the transaction
+    serialized.extend_from_slice(&[1, 2, 3]);
+
+    let view = TransactionView::new(&serialized).unwrap();
+    assert_eq!(view.as_bytes(), &serialized[..transaction_size]);
+    assert_eq!(view.version(), TransactionVersion::V2);
+    assert_eq!(view.fee(), 2);
+    assert_eq!(view.expiration(), 1234);
+    assert_eq!(
+        view.randomized_public_key(),
+        public_transaction.randomized_public_key().0.to_bytes()
+    );
+    assert_eq!(
+        *view.transaction_hash().as_bytes(),
+        *blake3::hash(&serialized[..transaction_size]).as_bytes()
+    );
+    assert_eq!(
+        view.transaction_hash(),
+        public_transaction.hash().expect("should hash transaction")
+    );
+
+    assert_eq!(view.spends_len(), 1);
+    assert_eq!(view.spend(1), None);
+    assert_eq!(
+        view.nullifiers().collect::<Vec<_>>(),
+        vec![public_transaction.spends()[0].nullifier()]
+    );
+
+    assert_eq!(view.outputs_len(), public_transaction.outputs().len());
+    for (index, output) in public_transaction.outputs().iter().enumerate() {
+        let mut merkle_note = vec![];
+        output.merkle_note().write(&mut merkle_note).unwrap();
+        assert_eq!(view.output_merkle_note(index).unwrap(), merkle_note);
+        assert_eq!(
+            view.output_note_commitment(index).unwrap(),
+            output.merkle_note().merkle_hash().0.to_bytes_le()
+        );
+    }
+
+    assert_eq!(view.mints_len(), 2);
+    for (index, mint) in public_transaction.mints().iter().enumerate() {
+        let decoded = MintDescription::read(view.mint(index).unwrap(), view.version()).unwrap();
+        assert_eq!(decoded.asset, mint.asset);
+        assert_eq!(decoded.transfer_ownership_to, mint.transfer_ownership_to);
+    }
+    assert_eq!(view.burns_len(), 1);
+    assert_eq!(
+        BurnDescription::read(view.burn(0).unwrap()).unwrap().value,
+        3
+    );
+    assert_eq!(view.binding_signature().len(), TRANSACTION_SIGNATURE_SIZE);
+
+    let decoded = view.decode().unwrap();
+    assert_eq!(
+        decoded.transaction_signature_hash().unwrap(),
+        public_transaction.transaction_signature_hash().unwrap()
+    );
+
+    // Every truncation of the transaction is rejected
+    for len in 0..transaction_size {
+        assert!(TransactionView::new(&serialized[..len]).is_err());
+    }
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_hash() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+    let in_note = Note::new(
+        spender_key.public_address(),
+        42,
+        "",
+        NATIVE_ASSET,
+        receiver_key.public_address(),
+    );
+    let out_note = Note::new(
+        receiver_key.public_address(),
+        40,
+        "",
+        NATIVE_ASSET,
+        spender_key.public_address(),
+    );
+    let witness = make_fake_witness(&in_note);
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note, &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+    let unsigned_transaction = transaction
+        .build(
+            spender_key.proof_authorizing_key,
+            spender_key.view_key().clone(),
+            spender_key.outgoing_view_key().clone(),
+            1,
+            Some(spender_key.public_address()),
+        )
+        .expect("should be able to build unsigned transaction");
+    let unsigned_hash = unsigned_transaction
+        .hash()
+        .expect("should hash unsigned transaction");
+    let signed_transaction = unsigned_transaction
+        .sign(&spender_key)
+        .expect("should be able to sign transaction");
+
+    assert_eq!(
+        signed_transaction.unsigned_hash().unwrap(),
+        unsigned_hash,
+        "signing should not change the unsigned hash"
+    );
+    assert_eq!(
+        *unsigned_hash.as_bytes(),
+        signed_transaction.transaction_signature_hash().unwrap()
+    );
+
+    let mut serialized = Vec::new();
+    signed_transaction
+        .write(&mut serialized)
+        .expect("should be able to serialize transaction");
+    let hash = signed_transaction.hash().expect("should hash transaction");
+    assert_eq!(*hash.as_bytes(), *blake3::hash(&serialized).as_bytes());
+    assert_ne!(hash, unsigned_hash);
+
+    let read_transaction =
+        Transaction::read(&serialized[..]).expect("should be able to read transaction");
+    assert_eq!(read_transaction.hash().unwrap(), hash);
+    assert_eq!(hash.to_string().parse::<TransactionId>().unwrap(), hash);
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_transaction_with_commitment_tree_witness() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    let mut tree = CommitmentTree::new();
+    let mut in_notes = vec![];
+    for value in 1..=5 {
+        let note = Note::new(
+            spender_key.public_address(),
+            value,
+            "",
+            NATIVE_ASSET,
+            receiver_key.public_address(),
+        );
+        tree.append(MerkleNoteHash::new(note.commitment_point()))
+            .unwrap();
+        in_notes.push(note);
+    }
+
+    let in_note = in_notes.remove(2);
+    let witness = tree.witness(2).unwrap();
+    assert_eq!(witness.root_hash, tree.root_hash().unwrap());
+
+    let out_note = Note::new(
+        receiver_key.public_address(),
+        2,
+        "",
+        NATIVE_ASSET,
+        spender_key.public_address(),
+    );
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    transaction.add_spend(in_note.clone(), &witness).unwrap();
+    transaction.add_output(out_note).unwrap();
+
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+
+    let spend = &public_transaction.spends()[0];
+    assert_eq!(spend.root_hash(), tree.root_hash().unwrap());
+    assert_eq!(spend.tree_size(), 5);
+    assert_eq!(
+        spend.nullifier(),
+        in_note.nullifier(spender_key.view_key(), 2)
+    );
+}
+
 #[test]
 #[cfg(feature = "transaction-proofs")]
 fn test_proposed_transaction_build() {
@@ -275,6 +1086,76 @@ fn test_proposed_transaction_build() {
     assert_eq!(received_note.sender, spender_key_clone.public_address());
 }
 
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_proposed_transaction_build_preserves_order() {
+    let spender_key = SaplingKey::generate_key();
+    let receiver_key = SaplingKey::generate_key();
+
+    let mut tree = CommitmentTree::new();
+    let mut in_notes = vec![];
+    for value in 10..14 {
+        let note = Note::new(
+            spender_key.public_address(),
+            value,
+            "",
+            NATIVE_ASSET,
+            receiver_key.public_address(),
+        );
+        tree.append(MerkleNoteHash::new(note.commitment_point()))
+            .unwrap();
+        in_notes.push(note);
+    }
+
+    let mut transaction = ProposedTransaction::new(TransactionVersion::latest());
+    for (position, note) in in_notes.iter().enumerate() {
+        let witness = tree.witness(position as u64).unwrap();
+        transaction.add_spend(note.clone(), &witness).unwrap();
+    }
+    for value in 1..5 {
+        let out_note = Note::new(
+            receiver_key.public_address(),
+            value,
+            "",
+            NATIVE_ASSET,
+            spender_key.public_address(),
+        );
+        transaction.add_output(out_note).unwrap();
+    }
+
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    transaction.set_thread_pool(Arc::new(thread_pool));
+    let public_transaction = transaction
+        .post(&spender_key, None, 1)
+        .expect("should be able to post transaction");
+    verify_transaction(&public_transaction).expect("Should be able to verify transaction");
+
+    for (position, (spend, note)) in public_transaction
+        .spends()
+        .iter()
+        .zip(in_notes.iter())
+        .enumerate()
+    {
+        assert_eq!(
+            spend.nullifier(),
+            note.nullifier(spender_key.view_key(), position as u64)
+        );
+    }
+
+    // The change note is appended after the requested outputs
+    assert_eq!(public_transaction.outputs().len(), 5);
+    for (output, value) in public_transaction.outputs().iter().zip(1..5) {
+        let received_note = output
+            .merkle_note()
+            .decrypt_note_for_owner(receiver_key.incoming_view_key())
+            .unwrap();
+        assert_eq!(received_note.value(), value);
+    }
+}
+
 #[test]
 #[cfg(feature = "transaction-proofs")]
 fn test_miners_fee() {
@@ -699,6 +1580,28 @@ fn test_batch_verify_wrong_mint_params() {
         &wrong_mint_vk,
     )
     .expect_err("Should not verify if mint verifying key is wrong");
+
+    // Both transactions contain a mint, so both should be reported as invalid
+    assert_eq!(
+        internal_batch_verify_transactions_with_failures(
+            [&transaction1, &transaction2],
+            &SAPLING.spend_verifying_key,
+            &SAPLING.output_verifying_key,
+            &wrong_mint_vk,
+        ),
+        vec![
+            TransactionVerificationFailure {
+                index: 0,
+                kind: IronfishErrorKind::InvalidMintProof,
+                context: None,
+            },
+            TransactionVerificationFailure {
+                index: 1,
+                kind: IronfishErrorKind::InvalidMintProof,
+                context: None,
+            }
+        ]
+    );
 }
 
 #[test]
@@ -771,12 +1674,58 @@ fn test_batch_verify() {
     batch_verify_transactions([&transaction1, &transaction2])
         .expect("should be able to verify transaction");
 
+    assert!(batch_verify_transactions_with_failures([&transaction1, &transaction2]).is_empty());
+
     transaction1.randomized_public_key = other_randomized_public_key;
 
     assert!(matches!(
         batch_verify_transactions([&transaction1, &transaction2]),
         Err(e) if matches!(e.kind, IronfishErrorKind::InvalidSpendSignature)
     ));
+    assert_eq!(
+        batch_verify_transactions_with_failures([&transaction1, &transaction2, &transaction2]),
+        vec![TransactionVerificationFailure {
+            index: 0,
+            kind: IronfishErrorKind::InvalidSpendSignature,
+            context: Some("spends[0]".to_string()),
+        }]
+    );
+}
+
+#[test]
+#[cfg(feature = "transaction-proofs")]
+fn test_batch_verify_with_exported_verifying_keys() {
+    let key = SaplingKey::generate_key();
+    let public_address = key.public_address();
+
+    let in_note = Note::new(public_address, 42, "", NATIVE_ASSET, public_address);
+    let out_note = Note::new(public_address, 40, "", NATIVE_ASSET, public_address);
+    let witness = make_fake_witness(&in_note);
+
+    let mut proposed_transaction = ProposedTransaction::new(TransactionVersion::latest());
+    proposed_transaction.add_spend(in_note, &witness).unwrap();
+    proposed_transaction.add_output(out_note).unwrap();
+    let transaction = proposed_transaction
+        .post(&key, None, 1)
+        .expect("should be able to post transaction");
+
+    let directory =
+        std::env::temp_dir().join(format!("ironfish-verifying-keys-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    SAPLING
+        .write_verifying_keys(&directory)
+        .expect("should be able to write verifying keys");
+    let verifier = SaplingVerifier::load_from_path(&directory);
+    std::fs::remove_dir_all(&directory).unwrap();
+    let verifier = verifier.expect("should be able to load verifying keys");
+
+    internal_batch_verify_transactions(
+        [&transaction],
+        &verifier.spend_verifying_key,
+        &verifier.output_verifying_key,
+        &verifier.mint_verifying_key,
+    )
+    .expect("should verify with the exported verifying keys");
 }
 
 #[test]
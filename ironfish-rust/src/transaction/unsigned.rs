@@ -29,7 +29,8 @@ use crate::{
 
 use super::{
     burns::BurnDescription, mints::UnsignedMintDescription, spends::UnsignedSpendDescription,
-    TransactionVersion, SIGNATURE_HASH_PERSONALIZATION, TRANSACTION_SIGNATURE_VERSION,
+    TransactionId, TransactionVersion, SIGNATURE_HASH_PERSONALIZATION,
+    TRANSACTION_SIGNATURE_VERSION,
 };
 
 #[derive(Clone)]
@@ -159,6 +160,13 @@ impl UnsignedTransaction {
         Ok(())
     }
 
+    /// The id of the transaction, which is the hash returned by
+    /// [`UnsignedTransaction::transaction_signature_hash`]. It is the same as
+    /// the [`Transaction::unsigned_hash`] of the signed transaction.
+    pub fn hash(&self) -> Result<TransactionId, IronfishError> {
+        self.transaction_signature_hash().map(TransactionId::from)
+    }
+
     /// Calculate a hash of the transaction data. This hash was signed by the
     /// private keys when the transaction was constructed, and will now be
     /// reconstructed to verify the signature.
@@ -338,4 +346,12 @@ impl UnsignedTransaction {
     pub fn burns(&self) -> &Vec<BurnDescription> {
         &self.burns
     }
+
+    pub fn fee(&self) -> i64 {
+        self.fee
+    }
+
+    pub fn expiration(&self) -> u32 {
+        self.expiration
+    }
 }
@@ -3,8 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use crate::{
-    errors::{IronfishError, IronfishErrorKind},
-    sapling_bls12::SAPLING,
+    errors::{ErrorContext, IronfishError, IronfishErrorKind},
+    sapling_bls12::installed_verifier,
     transaction::Transaction,
 };
 use blstrs::Bls12;
@@ -12,9 +12,13 @@ use ironfish_bellperson::groth16::{self, verify_proofs_batch, PreparedVerifyingK
 use ironfish_jubjub::ExtendedPoint;
 use rand::rngs::OsRng;
 
+#[cfg(feature = "transaction-proofs")]
+use crate::sapling_bls12::SAPLING;
+
 /// Helper function for verifying spend proof internally. Note that this is not
 /// called by verifiers as part of transaction verification. See
 /// [`super::batch_verify_transactions`]
+#[cfg(feature = "transaction-proofs")]
 pub(crate) fn verify_spend_proof(
     proof: &groth16::Proof<Bls12>,
     inputs: &[blstrs::Scalar],
@@ -29,6 +33,7 @@ pub(crate) fn verify_spend_proof(
 /// Helper function for verifying output proof internally. Note that this is not
 /// called by verifiers as part of transaction verification. See
 /// [`super::batch_verify_transactions`]
+#[cfg(feature = "transaction-proofs")]
 pub(crate) fn verify_output_proof(
     proof: &groth16::Proof<Bls12>,
     inputs: &[blstrs::Scalar],
@@ -43,6 +48,7 @@ pub(crate) fn verify_output_proof(
 /// Helper function for verifying mint proof internally. Note that this is not
 /// called by verifiers as part of transaction verification. See
 /// [`super::batch_verify_transactions`]
+#[cfg(feature = "transaction-proofs")]
 pub(crate) fn verify_mint_proof(
     proof: &groth16::Proof<Bls12>,
     inputs: &[blstrs::Scalar],
@@ -56,115 +62,280 @@ pub(crate) fn verify_mint_proof(
 
 /// A convenience wrapper method around [`batch_verify_transactions`] for single
 /// transactions
-#[cfg(feature = "transaction-proofs")]
 pub fn verify_transaction(transaction: &Transaction) -> Result<(), IronfishError> {
     batch_verify_transactions(std::iter::once(transaction))
 }
 
-#[cfg(feature = "transaction-proofs")]
-pub(super) fn internal_batch_verify_transactions<'a>(
-    transactions: impl IntoIterator<Item = &'a Transaction>,
-    spend_verifying_key: &PreparedVerifyingKey<Bls12>,
-    output_verifying_key: &PreparedVerifyingKey<Bls12>,
-    mint_verifying_key: &PreparedVerifyingKey<Bls12>,
-) -> Result<(), IronfishError> {
-    let mut spend_proofs = vec![];
-    let mut spend_public_inputs = vec![];
+/// The verifying keys for the spend, output and mint proofs: those of the
+/// verifier installed with [`crate::sapling_bls12::init_verifier_with`] if
+/// any, otherwise those of the global Sapling instance.
+fn verifying_keys() -> [&'static PreparedVerifyingKey<Bls12>; 3] {
+    match installed_verifier() {
+        Some(verifier) => [
+            &verifier.spend_verifying_key,
+            &verifier.output_verifying_key,
+            &verifier.mint_verifying_key,
+        ],
+        #[cfg(feature = "transaction-proofs")]
+        None => [
+            &SAPLING.spend_verifying_key,
+            &SAPLING.output_verifying_key,
+            &SAPLING.mint_verifying_key,
+        ],
+        #[cfg(not(feature = "transaction-proofs"))]
+        None => panic!("a verifier should be installed with `sapling_bls12::init_verifier_with`"),
+    }
+}
 
-    let mut output_proofs = vec![];
-    let mut output_public_inputs = vec![];
+/// Proofs and their public inputs collected from one or more transactions, so
+/// that they can be verified together in a single batch.
+#[derive(Default)]
+struct ProofBatch<'a> {
+    spend_proofs: Vec<&'a groth16::Proof<Bls12>>,
+    spend_public_inputs: Vec<Vec<blstrs::Scalar>>,
 
-    let mut mint_proofs = vec![];
-    let mut mint_public_inputs = vec![];
+    output_proofs: Vec<&'a groth16::Proof<Bls12>>,
+    output_public_inputs: Vec<Vec<blstrs::Scalar>>,
 
-    for transaction in transactions {
-        // Context to accumulate a signature of all the spends and outputs and
-        // guarantee they are part of this transaction, unmodified.
-        let mut binding_verification_key = ExtendedPoint::identity();
+    mint_proofs: Vec<&'a groth16::Proof<Bls12>>,
+    mint_public_inputs: Vec<Vec<blstrs::Scalar>>,
+}
 
-        let hash_to_verify_signature = transaction.transaction_signature_hash()?;
+impl<'a> ProofBatch<'a> {
+    fn extend(&mut self, other: &ProofBatch<'a>) {
+        self.spend_proofs.extend_from_slice(&other.spend_proofs);
+        self.spend_public_inputs
+            .extend_from_slice(&other.spend_public_inputs);
+        self.output_proofs.extend_from_slice(&other.output_proofs);
+        self.output_public_inputs
+            .extend_from_slice(&other.output_public_inputs);
+        self.mint_proofs.extend_from_slice(&other.mint_proofs);
+        self.mint_public_inputs
+            .extend_from_slice(&other.mint_public_inputs);
+    }
 
-        for spend in transaction.spends.iter() {
-            spend.partial_verify()?;
+    fn verify(
+        &self,
+        spend_verifying_key: &PreparedVerifyingKey<Bls12>,
+        output_verifying_key: &PreparedVerifyingKey<Bls12>,
+        mint_verifying_key: &PreparedVerifyingKey<Bls12>,
+    ) -> Result<(), IronfishError> {
+        if !self.spend_proofs.is_empty()
+            && !verify_proofs_batch(
+                spend_verifying_key,
+                &mut OsRng,
+                &self.spend_proofs[..],
+                &self.spend_public_inputs[..],
+            )?
+        {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidSpendProof));
+        }
+        if !self.output_proofs.is_empty()
+            && !verify_proofs_batch(
+                output_verifying_key,
+                &mut OsRng,
+                &self.output_proofs[..],
+                &self.output_public_inputs[..],
+            )?
+        {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidOutputProof));
+        }
+        if !self.mint_proofs.is_empty()
+            && !verify_proofs_batch(
+                mint_verifying_key,
+                &mut OsRng,
+                &self.mint_proofs[..],
+                &self.mint_public_inputs[..],
+            )?
+        {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidMintProof));
+        }
 
-            spend_proofs.push(&spend.proof);
-            spend_public_inputs.push(
-                spend
-                    .public_inputs(transaction.randomized_public_key())
-                    .to_vec(),
-            );
+        Ok(())
+    }
+}
+
+/// Run every check on a transaction except for the zero-knowledge proofs,
+/// which are instead added to `batch` so that they can be verified later.
+fn verify_transaction_without_proofs<'a>(
+    transaction: &'a Transaction,
+    batch: &mut ProofBatch<'a>,
+) -> Result<(), IronfishError> {
+    // Context to accumulate a signature of all the spends and outputs and
+    // guarantee they are part of this transaction, unmodified.
+    let mut binding_verification_key = ExtendedPoint::identity();
+
+    let hash_to_verify_signature = transaction.transaction_signature_hash()?;
 
-            binding_verification_key += spend.value_commitment;
+    for (index, spend) in transaction.spends.iter().enumerate() {
+        spend
+            .partial_verify()
+            .with_context(|| format!("spends[{}]", index))?;
 
-            spend.verify_signature(
+        batch.spend_proofs.push(&spend.proof);
+        batch.spend_public_inputs.push(
+            spend
+                .public_inputs(transaction.randomized_public_key())
+                .to_vec(),
+        );
+
+        binding_verification_key += spend.value_commitment;
+
+        spend
+            .verify_signature(
                 &hash_to_verify_signature,
                 transaction.randomized_public_key(),
-            )?;
-        }
+            )
+            .with_context(|| format!("spends[{}]", index))?;
+    }
 
-        for output in transaction.outputs.iter() {
-            output.partial_verify()?;
+    for (index, output) in transaction.outputs.iter().enumerate() {
+        output
+            .partial_verify()
+            .with_context(|| format!("outputs[{}]", index))?;
 
-            output_proofs.push(&output.proof);
-            output_public_inputs.push(
-                output
-                    .public_inputs(transaction.randomized_public_key())
-                    .to_vec(),
-            );
+        batch.output_proofs.push(&output.proof);
+        batch.output_public_inputs.push(
+            output
+                .public_inputs(transaction.randomized_public_key())
+                .to_vec(),
+        );
 
-            binding_verification_key -= output.merkle_note.value_commitment;
-        }
+        binding_verification_key -= output.merkle_note.value_commitment;
+    }
 
-        for mint in transaction.mints.iter() {
-            mint.partial_verify()?;
+    for (index, mint) in transaction.mints.iter().enumerate() {
+        mint.partial_verify()
+            .with_context(|| format!("mints[{}]", index))?;
 
-            mint_proofs.push(&mint.proof);
-            mint_public_inputs.push(
-                mint.public_inputs(transaction.randomized_public_key())
-                    .to_vec(),
-            );
+        batch.mint_proofs.push(&mint.proof);
+        batch.mint_public_inputs.push(
+            mint.public_inputs(transaction.randomized_public_key())
+                .to_vec(),
+        );
 
-            mint.verify_signature(
-                &hash_to_verify_signature,
-                transaction.randomized_public_key(),
-            )?;
-        }
+        mint.verify_signature(
+            &hash_to_verify_signature,
+            transaction.randomized_public_key(),
+        )
+        .with_context(|| format!("mints[{}]", index))?;
+    }
 
-        transaction.verify_binding_signature(&binding_verification_key)?;
+    transaction
+        .verify_binding_signature(&binding_verification_key)
+        .context("binding_signature")?;
+
+    Ok(())
+}
+
+pub(super) fn internal_batch_verify_transactions<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+    spend_verifying_key: &PreparedVerifyingKey<Bls12>,
+    output_verifying_key: &PreparedVerifyingKey<Bls12>,
+    mint_verifying_key: &PreparedVerifyingKey<Bls12>,
+) -> Result<(), IronfishError> {
+    let mut batch = ProofBatch::default();
+
+    for transaction in transactions {
+        verify_transaction_without_proofs(transaction, &mut batch)?;
     }
 
-    if !spend_proofs.is_empty()
-        && !verify_proofs_batch(
-            spend_verifying_key,
-            &mut OsRng,
-            &spend_proofs[..],
-            &spend_public_inputs[..],
-        )?
-    {
-        return Err(IronfishError::new(IronfishErrorKind::InvalidSpendProof));
+    batch.verify(
+        spend_verifying_key,
+        output_verifying_key,
+        mint_verifying_key,
+    )
+}
+
+/// A transaction that failed verification as part of a batch, identified by
+/// its position in the batch.
+#[derive(Debug, PartialEq)]
+pub struct TransactionVerificationFailure {
+    pub index: usize,
+    pub kind: IronfishErrorKind,
+    /// Where in the transaction the failure happened, if known. See
+    /// [`IronfishError::context_path`].
+    pub context: Option<String>,
+}
+
+pub(super) fn internal_batch_verify_transactions_with_failures<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+    spend_verifying_key: &PreparedVerifyingKey<Bls12>,
+    output_verifying_key: &PreparedVerifyingKey<Bls12>,
+    mint_verifying_key: &PreparedVerifyingKey<Bls12>,
+) -> Vec<TransactionVerificationFailure> {
+    let mut failures = vec![];
+    let mut batches = vec![];
+
+    for (index, transaction) in transactions.into_iter().enumerate() {
+        let mut batch = ProofBatch::default();
+        match verify_transaction_without_proofs(transaction, &mut batch) {
+            Ok(()) => batches.push((index, batch)),
+            Err(e) => failures.push(TransactionVerificationFailure {
+                index,
+                context: e.context_path(),
+                kind: e.kind,
+            }),
+        }
     }
-    if !output_proofs.is_empty()
-        && !verify_proofs_batch(
-            output_verifying_key,
-            &mut OsRng,
-            &output_proofs[..],
-            &output_public_inputs[..],
-        )?
-    {
-        return Err(IronfishError::new(IronfishErrorKind::InvalidOutputProof));
+
+    bisect_proof_failures(
+        &batches,
+        spend_verifying_key,
+        output_verifying_key,
+        mint_verifying_key,
+        &mut failures,
+    );
+
+    failures.sort_by_key(|failure| failure.index);
+    failures
+}
+
+/// Verify the proofs of all the given transactions in one batch. If the batch
+/// fails, split it in half and verify each half separately, until the
+/// transactions with invalid proofs are isolated.
+fn bisect_proof_failures(
+    batches: &[(usize, ProofBatch)],
+    spend_verifying_key: &PreparedVerifyingKey<Bls12>,
+    output_verifying_key: &PreparedVerifyingKey<Bls12>,
+    mint_verifying_key: &PreparedVerifyingKey<Bls12>,
+    failures: &mut Vec<TransactionVerificationFailure>,
+) {
+    if batches.is_empty() {
+        return;
     }
-    if !mint_proofs.is_empty()
-        && !verify_proofs_batch(
-            mint_verifying_key,
-            &mut OsRng,
-            &mint_proofs[..],
-            &mint_public_inputs[..],
-        )?
-    {
-        return Err(IronfishError::new(IronfishErrorKind::InvalidOutputProof));
+
+    let mut combined = ProofBatch::default();
+    for (_, batch) in batches {
+        combined.extend(batch);
     }
 
-    Ok(())
+    let result = combined.verify(
+        spend_verifying_key,
+        output_verifying_key,
+        mint_verifying_key,
+    );
+
+    match result {
+        Ok(()) => {}
+        Err(e) if batches.len() == 1 => failures.push(TransactionVerificationFailure {
+            index: batches[0].0,
+            context: e.context_path(),
+            kind: e.kind,
+        }),
+        Err(_) => {
+            let (left, right) = batches.split_at(batches.len() / 2);
+            for half in [left, right] {
+                bisect_proof_failures(
+                    half,
+                    spend_verifying_key,
+                    output_verifying_key,
+                    mint_verifying_key,
+                    failures,
+                );
+            }
+        }
+    }
 }
 
 /// Validate the transaction. Confirms that:
@@ -176,14 +347,32 @@ pub(super) fn internal_batch_verify_transactions<'a>(
 ///  *  The entire transaction was signed with a binding signature
 ///     containing those proofs (and only those proofs)
 ///
-#[cfg(feature = "transaction-proofs")]
 pub fn batch_verify_transactions<'a>(
     transactions: impl IntoIterator<Item = &'a Transaction>,
 ) -> Result<(), IronfishError> {
+    let [spend_verifying_key, output_verifying_key, mint_verifying_key] = verifying_keys();
     internal_batch_verify_transactions(
         transactions,
-        &SAPLING.spend_verifying_key,
-        &SAPLING.output_verifying_key,
-        &SAPLING.mint_verifying_key,
+        spend_verifying_key,
+        output_verifying_key,
+        mint_verifying_key,
+    )
+}
+
+/// Validate a batch of transactions, reporting which ones are invalid.
+///
+/// This performs the same checks as [`batch_verify_transactions`], and is just
+/// as fast when every transaction is valid. If the aggregated proof check
+/// fails, the batch is bisected to find the transactions that caused the
+/// failure. An empty result means that all transactions are valid.
+pub fn batch_verify_transactions_with_failures<'a>(
+    transactions: impl IntoIterator<Item = &'a Transaction>,
+) -> Vec<TransactionVerificationFailure> {
+    let [spend_verifying_key, output_verifying_key, mint_verifying_key] = verifying_keys();
+    internal_batch_verify_transactions_with_failures(
+        transactions,
+        spend_verifying_key,
+        output_verifying_key,
+        mint_verifying_key,
     )
 }
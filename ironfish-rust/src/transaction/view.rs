@@ -0,0 +1,321 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Read-only access to the fields of a serialized [`Transaction`], without
+//! decoding it.
+//!
+//! [`Transaction::read`] decodes and validates every point, proof and
+//! signature of a transaction, which is wasted work when only a few fields
+//! are needed, for example by an indexer that only looks at nullifiers and
+//! note commitments. A [`TransactionView`] only checks that the layout of
+//! the bytes is consistent with the counts of descriptions in the header,
+//! and returns fields as slices of the original bytes. Individual
+//! descriptions can be decoded with their own `read` functions, such as
+//! [`SpendDescription::read`](super::spends::SpendDescription::read).
+
+use crate::{
+    assets::asset::ASSET_LENGTH,
+    errors::{IronfishError, IronfishErrorKind},
+    keys::PUBLIC_ADDRESS_SIZE,
+    merkle_note::MERKLE_NOTE_SIZE,
+    transaction::{
+        burns::BURN_DESCRIPTION_SIZE,
+        mints::mint_description_size,
+        outputs::{OUTPUT_DESCRIPTION_SIZE, PROOF_SIZE},
+        spends::SPEND_DESCRIPTION_SIZE,
+        Transaction, TransactionId, TransactionVersion, TRANSACTION_FIXED_SIZE,
+        TRANSACTION_PUBLIC_KEY_SIZE, TRANSACTION_SIGNATURE_SIZE,
+    },
+};
+use byteorder::{ByteOrder, LittleEndian};
+use ironfish_zkp::Nullifier;
+
+const NUM_SPENDS_OFFSET: usize = 1;
+const NUM_OUTPUTS_OFFSET: usize = NUM_SPENDS_OFFSET + 8;
+const NUM_MINTS_OFFSET: usize = NUM_OUTPUTS_OFFSET + 8;
+const NUM_BURNS_OFFSET: usize = NUM_MINTS_OFFSET + 8;
+const FEE_OFFSET: usize = NUM_BURNS_OFFSET + 8;
+const EXPIRATION_OFFSET: usize = FEE_OFFSET + 8;
+const RANDOMIZED_PUBLIC_KEY_OFFSET: usize = EXPIRATION_OFFSET + 4;
+const HEADER_SIZE: usize = TRANSACTION_FIXED_SIZE - TRANSACTION_SIGNATURE_SIZE;
+
+/// Offset of the nullifier in a spend description, after the proof, the
+/// value commitment, the root hash and the tree size.
+const SPEND_NULLIFIER_OFFSET: usize = PROOF_SIZE as usize + 32 + 32 + 4;
+
+/// Offset of the note commitment in a merkle note, after the value
+/// commitment.
+const NOTE_COMMITMENT_OFFSET: usize = 32;
+
+/// Offset of the flag for the new owner in a mint description of a version
+/// that supports ownership transfers, after the proof, the asset, the value
+/// and the owner.
+const MINT_TRANSFER_FLAG_OFFSET: usize =
+    PROOF_SIZE as usize + ASSET_LENGTH + 8 + PUBLIC_ADDRESS_SIZE;
+
+/// A serialized [`Transaction`] whose fields are read on demand.
+#[derive(Clone, Debug)]
+pub struct TransactionView<'a> {
+    /// The bytes of the transaction, without any trailing bytes
+    bytes: &'a [u8],
+    version: TransactionVersion,
+    num_spends: usize,
+    num_outputs: usize,
+    /// Offset of each mint description, which vary in size
+    mint_offsets: Vec<usize>,
+    num_burns: usize,
+    burns_offset: usize,
+}
+
+/// Advance `offset` by the size of `count` items of `item_size` bytes,
+/// checking that they fit in `len` bytes.
+fn advance(
+    offset: usize,
+    count: usize,
+    item_size: usize,
+    len: usize,
+    context: &'static str,
+) -> Result<usize, IronfishError> {
+    count
+        .checked_mul(item_size)
+        .and_then(|size| offset.checked_add(size))
+        .filter(|end| *end <= len)
+        .ok_or_else(|| IronfishError::new(IronfishErrorKind::InvalidData).with_context(context))
+}
+
+impl<'a> TransactionView<'a> {
+    /// Check the layout of the transaction at the start of `bytes`. Bytes
+    /// after the end of the transaction are ignored, so that transactions
+    /// can be read one after the other from the same buffer.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, IronfishError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(IronfishError::new(IronfishErrorKind::InvalidData).with_context("header"));
+        }
+
+        let version = TransactionVersion::from_u8(bytes[0]).ok_or_else(|| {
+            IronfishError::new(IronfishErrorKind::InvalidTransactionVersion).with_context("version")
+        })?;
+        let read_count = |offset: usize, context| {
+            usize::try_from(LittleEndian::read_u64(&bytes[offset..]))
+                .map_err(|e| IronfishError::from(e).with_context(context))
+        };
+        let num_spends = read_count(NUM_SPENDS_OFFSET, "num_spends")?;
+        let num_outputs = read_count(NUM_OUTPUTS_OFFSET, "num_outputs")?;
+        let num_mints = read_count(NUM_MINTS_OFFSET, "num_mints")?;
+        let num_burns = read_count(NUM_BURNS_OFFSET, "num_burns")?;
+
+        let len = bytes.len();
+        let outputs_offset = advance(
+            HEADER_SIZE,
+            num_spends,
+            SPEND_DESCRIPTION_SIZE,
+            len,
+            "spends",
+        )?;
+        let mut offset = advance(
+            outputs_offset,
+            num_outputs,
+            OUTPUT_DESCRIPTION_SIZE,
+            len,
+            "outputs",
+        )?;
+
+        let mut mint_offsets = Vec::with_capacity(num_mints.min(len));
+        for index in 0..num_mints {
+            let transfers_ownership = version.has_mint_transfer_ownership_to()
+                && match bytes.get(offset + MINT_TRANSFER_FLAG_OFFSET) {
+                    Some(flag) => *flag != 0,
+                    None => {
+                        return Err(IronfishError::new(IronfishErrorKind::InvalidData)
+                            .with_context(format!("mints[{}]", index)))
+                    }
+                };
+
+            mint_offsets.push(offset);
+            offset = advance(
+                offset,
+                1,
+                mint_description_size(version, transfers_ownership),
+                len,
+                "mints",
+            )?;
+        }
+
+        let burns_offset = offset;
+        let offset = advance(burns_offset, num_burns, BURN_DESCRIPTION_SIZE, len, "burns")?;
+        let end = advance(
+            offset,
+            1,
+            TRANSACTION_SIGNATURE_SIZE,
+            len,
+            "binding_signature",
+        )?;
+
+        Ok(TransactionView {
+            bytes: &bytes[..end],
+            version,
+            num_spends,
+            num_outputs,
+            mint_offsets,
+            num_burns,
+            burns_offset,
+        })
+    }
+
+    /// The bytes of the transaction.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Decode the whole transaction.
+    pub fn decode(&self) -> Result<Transaction, IronfishError> {
+        Transaction::read(self.bytes)
+    }
+
+    /// The id of the transaction, the same as [`Transaction::hash`].
+    pub fn transaction_hash(&self) -> TransactionId {
+        TransactionId::from(*blake3::hash(self.bytes).as_bytes())
+    }
+
+    pub fn version(&self) -> TransactionVersion {
+        self.version
+    }
+
+    pub fn fee(&self) -> i64 {
+        LittleEndian::read_i64(&self.bytes[FEE_OFFSET..])
+    }
+
+    pub fn expiration(&self) -> u32 {
+        LittleEndian::read_u32(&self.bytes[EXPIRATION_OFFSET..])
+    }
+
+    pub fn randomized_public_key(&self) -> &'a [u8] {
+        &self.bytes[RANDOMIZED_PUBLIC_KEY_OFFSET
+            ..RANDOMIZED_PUBLIC_KEY_OFFSET + TRANSACTION_PUBLIC_KEY_SIZE]
+    }
+
+    pub fn binding_signature(&self) -> &'a [u8] {
+        &self.bytes[self.binding_signature_offset()..]
+    }
+
+    pub fn spends_len(&self) -> usize {
+        self.num_spends
+    }
+
+    pub fn outputs_len(&self) -> usize {
+        self.num_outputs
+    }
+
+    pub fn mints_len(&self) -> usize {
+        self.mint_offsets.len()
+    }
+
+    pub fn burns_len(&self) -> usize {
+        self.num_burns
+    }
+
+    /// Offset of the first spend description.
+    pub fn spends_offset(&self) -> usize {
+        HEADER_SIZE
+    }
+
+    /// Offset of the first output description.
+    pub fn outputs_offset(&self) -> usize {
+        self.spends_offset() + self.num_spends * SPEND_DESCRIPTION_SIZE
+    }
+
+    /// Offset of the first mint description.
+    pub fn mints_offset(&self) -> usize {
+        self.outputs_offset() + self.num_outputs * OUTPUT_DESCRIPTION_SIZE
+    }
+
+    /// Offset of the first burn description.
+    pub fn burns_offset(&self) -> usize {
+        self.burns_offset
+    }
+
+    pub fn binding_signature_offset(&self) -> usize {
+        self.bytes.len() - TRANSACTION_SIGNATURE_SIZE
+    }
+
+    /// The bytes of a spend description, which can be decoded with
+    /// [`SpendDescription::read`](super::spends::SpendDescription::read).
+    pub fn spend(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.num_spends {
+            return None;
+        }
+
+        let offset = self.spends_offset() + index * SPEND_DESCRIPTION_SIZE;
+        Some(&self.bytes[offset..offset + SPEND_DESCRIPTION_SIZE])
+    }
+
+    pub fn spend_nullifier(&self, index: usize) -> Option<Nullifier> {
+        let spend = self.spend(index)?;
+        let mut nullifier = Nullifier([0; 32]);
+        nullifier
+            .0
+            .copy_from_slice(&spend[SPEND_NULLIFIER_OFFSET..SPEND_NULLIFIER_OFFSET + 32]);
+
+        Some(nullifier)
+    }
+
+    /// The nullifiers of the spends, in order.
+    pub fn nullifiers(&self) -> impl Iterator<Item = Nullifier> + '_ {
+        (0..self.num_spends).filter_map(|index| self.spend_nullifier(index))
+    }
+
+    /// The bytes of an output description, which can be decoded with
+    /// [`OutputDescription::read`](super::outputs::OutputDescription::read).
+    pub fn output(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.num_outputs {
+            return None;
+        }
+
+        let offset = self.outputs_offset() + index * OUTPUT_DESCRIPTION_SIZE;
+        Some(&self.bytes[offset..offset + OUTPUT_DESCRIPTION_SIZE])
+    }
+
+    /// The bytes of the merkle note of an output, which can be decoded with
+    /// [`MerkleNote::read`](crate::MerkleNote::read).
+    pub fn output_merkle_note(&self, index: usize) -> Option<&'a [u8]> {
+        self.output(index)
+            .map(|output| &output[PROOF_SIZE as usize..PROOF_SIZE as usize + MERKLE_NOTE_SIZE])
+    }
+
+    /// The bytes of the note commitment of an output.
+    pub fn output_note_commitment(&self, index: usize) -> Option<&'a [u8]> {
+        self.output_merkle_note(index)
+            .map(|note| &note[NOTE_COMMITMENT_OFFSET..NOTE_COMMITMENT_OFFSET + 32])
+    }
+
+    /// The bytes of the merkle notes of the outputs, in order.
+    pub fn merkle_notes(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        (0..self.num_outputs).filter_map(|index| self.output_merkle_note(index))
+    }
+
+    /// The bytes of a mint description, which can be decoded with
+    /// [`MintDescription::read`](super::mints::MintDescription::read) and
+    /// the version of the transaction.
+    pub fn mint(&self, index: usize) -> Option<&'a [u8]> {
+        let start = *self.mint_offsets.get(index)?;
+        let end = self
+            .mint_offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.burns_offset);
+
+        Some(&self.bytes[start..end])
+    }
+
+    /// The bytes of a burn description, which can be decoded with
+    /// [`BurnDescription::read`](super::burns::BurnDescription::read).
+    pub fn burn(&self, index: usize) -> Option<&'a [u8]> {
+        if index >= self.num_burns {
+            return None;
+        }
+
+        let offset = self.burns_offset + index * BURN_DESCRIPTION_SIZE;
+        Some(&self.bytes[offset..offset + BURN_DESCRIPTION_SIZE])
+    }
+}